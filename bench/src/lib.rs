@@ -0,0 +1,133 @@
+//! 用来测量数学库计算单元(compute units)消耗的链上程序
+//!
+//! 每条指令只调用一次被测函数,参数从指令数据中读取,结果交给 `black_box`,
+//! 避免编译器在编译期算出结果或者直接删掉调用。
+//! 交易消耗的计算单元减去 [`BenchInstruction::Noop`] 的消耗就是被测函数本身的消耗,
+//! 见 `tests/compute_units.rs`
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use my_clmm::libraries::{tick_math, MulDiv, U128, U256};
+use std::hint::black_box;
+
+declare_id!("844s9jLyAmQggpDmujk4iYL9aEZnahns9cL8Skrj9tki");
+
+/// 被测的函数以及它的参数
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum BenchInstruction {
+    /// 只解析指令数据,作为其他指令的基准
+    Noop,
+    GetSqrtPriceAtTick {
+        tick: i32,
+    },
+    GetTickAtSqrtPrice {
+        sqrt_price_x64: u128,
+    },
+    /// `u64` 的 mul_div, `round_up` 为 true 时调用 mul_div_ceil
+    MulDivU64 {
+        a: u64,
+        b: u64,
+        denom: u64,
+        round_up: bool,
+    },
+    MulDivU128 {
+        a: u128,
+        b: u128,
+        denom: u128,
+        round_up: bool,
+    },
+    /// 参数是 U256 的四个 u64,低位在前
+    MulDivU256 {
+        a: [u64; 4],
+        b: [u64; 4],
+        denom: [u64; 4],
+        round_up: bool,
+    },
+    /// 原生 u128 的 a * b / denom,用来和 `MulDivU128` 对比,乘积必须不溢出 u128
+    NativeMulDivU128 {
+        a: u128,
+        b: u128,
+        denom: u128,
+    },
+}
+
+impl BenchInstruction {
+    pub fn instruction(&self) -> anchor_lang::solana_program::instruction::Instruction {
+        anchor_lang::solana_program::instruction::Instruction {
+            program_id: id(),
+            accounts: vec![],
+            data: self.try_to_vec().unwrap(),
+        }
+    }
+}
+
+#[cfg(not(feature = "no-entrypoint"))]
+anchor_lang::solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = BenchInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        BenchInstruction::Noop => {
+            black_box(());
+        }
+        BenchInstruction::GetSqrtPriceAtTick { tick } => {
+            black_box(tick_math::get_sqrt_price_at_tick(black_box(tick))?);
+        }
+        BenchInstruction::GetTickAtSqrtPrice { sqrt_price_x64 } => {
+            black_box(tick_math::get_tick_at_sqrt_price(black_box(
+                sqrt_price_x64,
+            ))?);
+        }
+        BenchInstruction::MulDivU64 {
+            a,
+            b,
+            denom,
+            round_up,
+        } => {
+            black_box(mul_div(a, b, denom, round_up)?);
+        }
+        BenchInstruction::MulDivU128 {
+            a,
+            b,
+            denom,
+            round_up,
+        } => {
+            black_box(mul_div(
+                U128::from(a),
+                U128::from(b),
+                U128::from(denom),
+                round_up,
+            )?);
+        }
+        BenchInstruction::MulDivU256 {
+            a,
+            b,
+            denom,
+            round_up,
+        } => {
+            black_box(mul_div(U256(a), U256(b), U256(denom), round_up)?);
+        }
+        BenchInstruction::NativeMulDivU128 { a, b, denom } => {
+            black_box(
+                black_box(a)
+                    .checked_mul(b)
+                    .and_then(|product| product.checked_div(denom))
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn mul_div<T: MulDiv<Output = T>>(a: T, b: T, denom: T, round_up: bool) -> Result<T> {
+    let result = if round_up {
+        black_box(a).mul_div_ceil(b, denom)
+    } else {
+        black_box(a).mul_div_floor(b, denom)
+    };
+    result.ok_or_else(|| ProgramError::ArithmeticOverflow.into())
+}
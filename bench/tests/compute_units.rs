@@ -0,0 +1,371 @@
+//! 记录数学库和指令的计算单元消耗,并输出一份 JSON 报告
+//!
+//! 需要先编译出 SBF 程序,原生运行的程序不会统计计算单元,所以测试标记为 `#[ignore]`:
+//!
+//! ```sh
+//! cargo build-sbf
+//! cargo test-sbf -p my-clmm-bench -- --ignored --nocapture
+//! ```
+//!
+//! 报告默认写到 `target/compute_units.json`,可以用 `COMPUTE_UNITS_REPORT` 环境变量指定其他路径。
+//! 找不到 `my_clmm.so` 或 `my_clmm_bench.so` 时测试直接失败,不会在没有报告的情况下通过
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{system_program, AccountSerialize};
+use anchor_spl::token::spl_token;
+use my_clmm::client;
+use my_clmm::instructions::InitializeRewardParam;
+use my_clmm::libraries::{fixed_point_64, tick_math, U256};
+use my_clmm::states::*;
+use my_clmm_bench::BenchInstruction;
+use serde_json::json;
+use solana_program_test::{find_file, tokio, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::path::PathBuf;
+
+/// 池子创建者和奖励提供者
+struct Fixture {
+    creator: Keypair,
+    amm_config: Pubkey,
+    token_mint_0: Pubkey,
+    token_mint_1: Pubkey,
+    reward_mint: Pubkey,
+    funder_token_account: Pubkey,
+}
+
+struct Measurement {
+    name: String,
+    compute_units: u64,
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF programs, run `cargo build-sbf` first"]
+async fn compute_units() {
+    for program in ["my_clmm.so", "my_clmm_bench.so"] {
+        assert!(
+            find_file(program).is_some(),
+            "{} not found, run `cargo build-sbf` first",
+            program
+        );
+    }
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program("my_clmm", my_clmm::id(), None);
+    program_test.add_program("my_clmm_bench", my_clmm_bench::id(), None);
+    let fixture = add_fixture_accounts(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+
+    let mut measurements = vec![];
+    let noop = measure(&mut context, &[BenchInstruction::Noop.instruction()], &[]).await;
+
+    let ticks = [
+        0,
+        1,
+        -1,
+        100_000,
+        -100_000,
+        tick_math::MIN_TICK,
+        tick_math::MAX_TICK,
+    ];
+    for tick in ticks {
+        let compute_units = measure(
+            &mut context,
+            &[BenchInstruction::GetSqrtPriceAtTick { tick }.instruction()],
+            &[],
+        )
+        .await;
+        measurements.push(Measurement {
+            name: format!("get_sqrt_price_at_tick({})", tick),
+            compute_units: compute_units.saturating_sub(noop),
+        });
+    }
+    // get_tick_at_sqrt_price 的定义域不包含 MAX_SQRT_PRICE_X64
+    for tick in ticks
+        .into_iter()
+        .filter(|tick| *tick != tick_math::MAX_TICK)
+    {
+        let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(tick).unwrap();
+        let compute_units = measure(
+            &mut context,
+            &[BenchInstruction::GetTickAtSqrtPrice { sqrt_price_x64 }.instruction()],
+            &[],
+        )
+        .await;
+        measurements.push(Measurement {
+            name: format!("get_tick_at_sqrt_price({})", sqrt_price_x64),
+            compute_units: compute_units.saturating_sub(noop),
+        });
+    }
+
+    for round_up in [false, true] {
+        let suffix = if round_up { "ceil" } else { "floor" };
+        let cases = [
+            (
+                format!("mul_div_{}_u64", suffix),
+                BenchInstruction::MulDivU64 {
+                    a: u64::MAX / 3,
+                    b: 1_000_003,
+                    denom: 1_000_007,
+                    round_up,
+                },
+            ),
+            (
+                format!("mul_div_{}_u128", suffix),
+                BenchInstruction::MulDivU128 {
+                    a: u128::MAX / 3,
+                    b: fixed_point_64::Q64 + 12345,
+                    denom: fixed_point_64::Q64 + 67890,
+                    round_up,
+                },
+            ),
+            (
+                format!("mul_div_{}_u256", suffix),
+                BenchInstruction::MulDivU256 {
+                    a: (U256::MAX / 3).0,
+                    b: U256::from(u128::MAX - 12345).0,
+                    denom: U256::from(u128::MAX - 67890).0,
+                    round_up,
+                },
+            ),
+        ];
+        for (name, instruction) in cases {
+            let compute_units = measure(&mut context, &[instruction.instruction()], &[]).await;
+            measurements.push(Measurement {
+                name,
+                compute_units: compute_units.saturating_sub(noop),
+            });
+        }
+    }
+    // 乘积不溢出 u128 时,对比 U128 和原生 u128
+    let (a, b, denom) = (u64::MAX as u128, 1_000_003, 1_000_007);
+    for (name, instruction) in [
+        (
+            "mul_div_floor_u128_small",
+            BenchInstruction::MulDivU128 {
+                a,
+                b,
+                denom,
+                round_up: false,
+            },
+        ),
+        (
+            "native_mul_div_u128_small",
+            BenchInstruction::NativeMulDivU128 { a, b, denom },
+        ),
+    ] {
+        let compute_units = measure(&mut context, &[instruction.instruction()], &[]).await;
+        measurements.push(Measurement {
+            name: name.to_string(),
+            compute_units: compute_units.saturating_sub(noop),
+        });
+    }
+
+    let create_pool = client::create_pool(
+        fixture.creator.pubkey(),
+        fixture.amm_config,
+        fixture.token_mint_0,
+        spl_token::id(),
+        fixture.token_mint_1,
+        spl_token::id(),
+        tick_math::get_sqrt_price_at_tick(0).unwrap(),
+        0,
+        client::CreatePoolOptions::default(),
+    );
+    let compute_units = measure(&mut context, &[create_pool], &[&fixture.creator]).await;
+    measurements.push(Measurement {
+        name: "create_pool".to_string(),
+        compute_units,
+    });
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let open_time = clock.unix_timestamp as u64 + 60;
+    let param = InitializeRewardParam {
+        open_time,
+        end_time: open_time + reward_period_limit::MIN_REWARD_PERIOD,
+        emissions_per_second_x64: fixed_point_64::Q64,
+    };
+    let pool_state = client::pool_address(
+        &fixture.amm_config,
+        &fixture.token_mint_0,
+        &fixture.token_mint_1,
+    )
+    .0;
+    let initialize_reward = client::initialize_reward(
+        fixture.creator.pubkey(),
+        fixture.funder_token_account,
+        fixture.amm_config,
+        pool_state,
+        fixture.reward_mint,
+        spl_token::id(),
+        param,
+        false,
+    );
+    let compute_units = measure(&mut context, &[initialize_reward], &[&fixture.creator]).await;
+    measurements.push(Measurement {
+        name: "initialize_reward".to_string(),
+        compute_units,
+    });
+
+    write_report(&measurements, noop);
+}
+
+/// 发送只包含 `instructions` 的交易,返回它消耗的计算单元
+async fn measure(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> u64 {
+    // 相同的交易会被当作重复交易拒绝,每次都使用新的 blockhash
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    let metadata = result.metadata.unwrap();
+    assert!(
+        result.result.is_ok(),
+        "transaction failed: {:?}\n{}",
+        result.result,
+        metadata.log_messages.join("\n")
+    );
+    metadata.compute_units_consumed
+}
+
+fn add_fixture_accounts(program_test: &mut ProgramTest) -> Fixture {
+    let creator = Keypair::new();
+    program_test.add_account(
+        creator.pubkey(),
+        Account::new(100_000_000_000, 0, &system_program::ID),
+    );
+
+    let amm_config = client::amm_config_address(0);
+    add_anchor_account(
+        program_test,
+        amm_config.0,
+        &AmmConfig {
+            bump: amm_config.1,
+            owner: creator.pubkey(),
+            trade_fee_rate: 2500,
+            protocol_fee_rate: 120_000,
+            fund_fee_rate: 40_000,
+            tick_spacing: 60,
+            ..Default::default()
+        },
+    );
+    let operation_state = client::operation_address();
+    add_anchor_account(
+        program_test,
+        operation_state.0,
+        &OperationState {
+            bump: operation_state.1,
+            ..Default::default()
+        },
+    );
+
+    let mut mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    mints.sort();
+    let reward_mint = Pubkey::new_unique();
+    for mint in mints.iter().chain([&reward_mint]) {
+        add_packed_account(
+            program_test,
+            *mint,
+            spl_token::state::Mint {
+                mint_authority: Some(creator.pubkey()).into(),
+                supply: u64::MAX / 2,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: None.into(),
+            },
+        );
+    }
+    let funder_token_account = Pubkey::new_unique();
+    add_packed_account(
+        program_test,
+        funder_token_account,
+        spl_token::state::Account {
+            mint: reward_mint,
+            owner: creator.pubkey(),
+            amount: u64::MAX / 2,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        },
+    );
+
+    Fixture {
+        creator,
+        amm_config: amm_config.0,
+        token_mint_0: mints[0],
+        token_mint_1: mints[1],
+        reward_mint,
+        funder_token_account,
+    }
+}
+
+fn add_anchor_account<T: AccountSerialize>(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    account: &T,
+) {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: my_clmm::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+fn add_packed_account<T: Pack>(program_test: &mut ProgramTest, address: Pubkey, account: T) {
+    let mut data = vec![0; T::LEN];
+    account.pack_into_slice(&mut data);
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+/// 数学函数的消耗已经减去了 `noop` 指令的基准消耗,指令的消耗是整笔交易的消耗
+fn write_report(measurements: &[Measurement], noop: u64) {
+    let path = std::env::var("COMPUTE_UNITS_REPORT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/compute_units.json")
+        });
+    let report = json!({
+        "baseline_compute_units": noop,
+        "cases": measurements
+            .iter()
+            .map(|item| json!({ "name": item.name, "compute_units": item.compute_units }))
+            .collect::<Vec<_>>(),
+    });
+    for item in measurements {
+        println!("{:<48} {:>8}", item.name, item.compute_units);
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&report).unwrap()).unwrap();
+    println!("compute unit report written to {}", path.display());
+}
@@ -0,0 +1,33 @@
+//! 从文件或标准输入读取交易日志,把 my-clmm 发出的事件输出为 JSON lines,输入格式见 `my_clmm_cli::indexer`
+use anyhow::Context;
+use clap::Parser;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "my-clmm-indexer",
+    version,
+    about = "Decode my-clmm events into JSON lines"
+)]
+struct Args {
+    /// 输入文件,默认读取标准输入
+    input: Option<PathBuf>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let mut out = io::stdout().lock();
+    match args.input {
+        Some(path) => {
+            let file =
+                File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+            my_clmm_cli::indexer::index(BufReader::new(file), &mut out)?;
+        }
+        None => {
+            my_clmm_cli::indexer::index(io::stdin().lock(), &mut out)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,401 @@
+//! 把解析后的账户转换成人可读的 JSON 或 CSV
+//!
+//! - Pubkey 使用 base58,u128 使用字符串,避免 JSON 解析时丢失精度
+//! - Q64.64 的字段同时输出换算后的小数
+//! - 池子的价格按两种代币的 decimals 换算
+//! - 状态位展开成名字,奖励输出状态的名字
+//! - 预言机的环形缓冲区按时间从旧到新输出,跳过还没写入的记录
+//!
+//! CSV 把 JSON 展开成 `field,value` 两列,嵌套的字段用 `.` 连接,例如 `reward_infos.0.state`
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use my_clmm::client::ClmmAccount;
+use my_clmm::libraries::fixed_point_64;
+use my_clmm::states::*;
+use serde_json::{json, Value};
+
+/// `operation_role` 中每个 bit 的名字
+const OPERATION_ROLES: [(u8, &str); 3] = [
+    (operation_role::REWARD_MANAGER, "reward_manager"),
+    (operation_role::POOL_PAUSER, "pool_pauser"),
+    (operation_role::WHITELIST_MANAGER, "whitelist_manager"),
+];
+
+/// 账户的类型、地址以及所有字段
+pub fn account_json(address: &Pubkey, account: &ClmmAccount) -> Value {
+    let mut value = match account {
+        ClmmAccount::AmmConfig(amm_config) => amm_config_json(amm_config),
+        ClmmAccount::PoolState(pool_state) => pool_state_json(pool_state),
+        ClmmAccount::ObservationState(observation_state) => {
+            observation_state_json(observation_state)
+        }
+        ClmmAccount::TickArrayBitmapExtension(extension) => bitmap_extension_json(extension),
+        ClmmAccount::TickArrayState(tick_array) => tick_array_json(tick_array),
+        ClmmAccount::OperationState(operation_state) => operation_state_json(operation_state),
+        ClmmAccount::PoolCreatorPermission(permission) => json!({
+            "bump": permission.bump,
+            "amm_config": permission.amm_config.to_string(),
+            "creator": permission.creator.to_string(),
+        }),
+        ClmmAccount::SupportMintAssociated(support_mint) => support_mint_json(support_mint),
+    };
+    let fields = value.as_object_mut().unwrap();
+    fields.insert("address".to_string(), json!(address.to_string()));
+    fields.insert("type".to_string(), json!(account.name()));
+    value
+}
+
+fn amm_config_json(amm_config: &AmmConfig) -> Value {
+    json!({
+        "bump": amm_config.bump,
+        "index": amm_config.index,
+        "owner": amm_config.owner.to_string(),
+        "trade_fee_rate": amm_config.trade_fee_rate,
+        "protocol_fee_rate": amm_config.protocol_fee_rate,
+        "fund_fee_rate": amm_config.fund_fee_rate,
+        "trade_fee_percent": fee_percent(amm_config.trade_fee_rate),
+        "protocol_fee_percent": fee_percent(amm_config.protocol_fee_rate),
+        "fund_fee_percent": fee_percent(amm_config.fund_fee_rate),
+        "tick_spacing": amm_config.tick_spacing,
+        "fund_owner": amm_config.fund_owner.to_string(),
+        "status": {
+            "raw": amm_config.status,
+            "create_pool_disabled": amm_config.is_create_pool_disabled(),
+            "create_pool_permissioned": amm_config.is_create_pool_permissioned(),
+        },
+    })
+}
+
+fn pool_state_json(pool_state: &PoolState) -> Value {
+    let status = pool_state.status;
+    let price = pool_state.price();
+    let reward_infos = { pool_state.reward_infos }
+        .iter()
+        .map(reward_info_json)
+        .collect::<Vec<_>>();
+    json!({
+        "bump": pool_state.bump[0],
+        "amm_config": ({ pool_state.amm_config }).to_string(),
+        "owner": ({ pool_state.owner }).to_string(),
+        "token_mint_0": ({ pool_state.token_mint_0 }).to_string(),
+        "token_mint_1": ({ pool_state.token_mint_1 }).to_string(),
+        "token_vault_0": ({ pool_state.token_vault_0 }).to_string(),
+        "token_vault_1": ({ pool_state.token_vault_1 }).to_string(),
+        "observation_key": ({ pool_state.observation_key }).to_string(),
+        "mint_decimals_0": pool_state.mint_decimals_0,
+        "mint_decimals_1": pool_state.mint_decimals_1,
+        "tick_spacing": ({ pool_state.tick_spacing }),
+        "liquidity": ({ pool_state.liquidity }).to_string(),
+        "sqrt_price_x64": ({ pool_state.sqrt_price_x64 }).to_string(),
+        "price": price,
+        "inverse_price": if price > 0.0 { 1.0 / price } else { 0.0 },
+        "tick_current": ({ pool_state.tick_current }),
+        "fee_growth_global_0_x64": ({ pool_state.fee_growth_global_0_x64 }).to_string(),
+        "fee_growth_global_1_x64": ({ pool_state.fee_growth_global_1_x64 }).to_string(),
+        "protocol_fees_token_0": ({ pool_state.protocol_fees_token_0 }),
+        "protocol_fees_token_1": ({ pool_state.protocol_fees_token_1 }),
+        "swap_in_amount_token_0": ({ pool_state.swap_in_amount_token_0 }).to_string(),
+        "swap_out_amount_token_1": ({ pool_state.swap_out_amount_token_1 }).to_string(),
+        "swap_in_amount_token_1": ({ pool_state.swap_in_amount_token_1 }).to_string(),
+        "swap_out_amount_token_0": ({ pool_state.swap_out_amount_token_0 }).to_string(),
+        "status": {
+            "raw": status,
+            "disabled": (0..u8::BITS as u8)
+                .filter(|bit| status & (1 << bit) != 0)
+                .filter_map(|bit| PoolStatusBitIndex::try_from(bit).ok())
+                .map(|bit| snake_case(&format!("{:?}", bit)))
+                .collect::<Vec<_>>(),
+        },
+        "reward_infos": reward_infos,
+        "tick_array_bitmap": ({ pool_state.tick_array_bitmap })
+            .iter()
+            .map(|word| format!("{:#018x}", word))
+            .collect::<Vec<_>>(),
+        "total_fees_token_0": ({ pool_state.total_fees_token_0 }),
+        "total_fees_claimed_token_0": ({ pool_state.total_fees_claimed_token_0 }),
+        "total_fees_token_1": ({ pool_state.total_fees_token_1 }),
+        "total_fees_claimed_token_1": ({ pool_state.total_fees_claimed_token_1 }),
+        "fund_fees_token_0": ({ pool_state.fund_fees_token_0 }),
+        "fund_fees_token_1": ({ pool_state.fund_fees_token_1 }),
+        "open_time": ({ pool_state.open_time }),
+        "recent_epoch": ({ pool_state.recent_epoch }),
+    })
+}
+
+fn reward_info_json(reward_info: &RewardInfo) -> Value {
+    let emissions_per_second_x64 = reward_info.emissions_per_second_x64;
+    json!({
+        "state": reward_state_name(reward_info.reward_state),
+        "open_time": ({ reward_info.open_time }),
+        "end_time": ({ reward_info.end_time }),
+        "last_update_time": ({ reward_info.last_update_time }),
+        "emissions_per_second_x64": emissions_per_second_x64.to_string(),
+        "emissions_per_second": q64_to_f64(emissions_per_second_x64),
+        "reward_total_emissioned": ({ reward_info.reward_total_emissioned }),
+        "reward_claimed": ({ reward_info.reward_claimed }),
+        "token_mint": ({ reward_info.token_mint }).to_string(),
+        "token_vault": ({ reward_info.token_vault }).to_string(),
+        "authority": ({ reward_info.authority }).to_string(),
+        "reward_growth_global_x64": ({ reward_info.reward_growth_global_x64 }).to_string(),
+    })
+}
+
+/// `RewardState` 的名字,超出范围时返回 `Unknown`
+pub fn reward_state_name(reward_state: u8) -> String {
+    match RewardState::try_from(reward_state) {
+        Ok(reward_state) => format!("{:?}", reward_state),
+        Err(_) => "Unknown".to_string(),
+    }
+}
+
+fn observation_state_json(observation_state: &ObservationState) -> Value {
+    json!({
+        "initialized": observation_state.initialized,
+        "recent_epoch": ({ observation_state.recent_epoch }),
+        "observation_index": ({ observation_state.observation_index }),
+        "pool_id": ({ observation_state.pool_id }).to_string(),
+        "observations": chronological_observations(observation_state)
+            .iter()
+            .map(|observation| json!({
+                "block_timestamp": ({ observation.block_timestamp }),
+                "tick_cumulative": ({ observation.tick_cumulative }),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// 环形缓冲区中 `observation_index` 是最新的记录,它的下一个是最旧的记录
+pub fn chronological_observations(observation_state: &ObservationState) -> Vec<Observation> {
+    let observations = { observation_state.observations };
+    let newest = usize::from(observation_state.observation_index) % OBSERVATION_NUM;
+    (1..=OBSERVATION_NUM)
+        .map(|offset| observations[(newest + offset) % OBSERVATION_NUM])
+        .filter(|observation| observation.block_timestamp != 0)
+        .collect()
+}
+
+fn bitmap_extension_json(extension: &TickArrayBitmapExtension) -> Value {
+    let words = |bitmap: [[u64; 8]; 14]| {
+        bitmap
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|word| format!("{:#018x}", word))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    json!({
+        "pool_id": ({ extension.pool_id }).to_string(),
+        "positive_tick_array_bitmap": words(extension.positive_tick_array_bitmap),
+        "negative_tick_array_bitmap": words(extension.negative_tick_array_bitmap),
+    })
+}
+
+/// 只输出已经初始化的 tick
+fn tick_array_json(tick_array: &TickArrayState) -> Value {
+    json!({
+        "pool_id": ({ tick_array.pool_id }).to_string(),
+        "start_tick_index": ({ tick_array.start_tick_index }),
+        "initialized_tick_count": tick_array.initialized_tick_count,
+        "recent_epoch": ({ tick_array.recent_epoch }),
+        "ticks": ({ tick_array.ticks })
+            .iter()
+            .filter(|tick| tick.is_initialized())
+            .map(|tick| json!({
+                "tick": ({ tick.tick }),
+                "liquidity_net": ({ tick.liquidity_net }).to_string(),
+                "liquidity_gross": ({ tick.liquidity_gross }).to_string(),
+                "fee_growth_outside_0": q64_to_f64(tick.fee_growth_outside_0_x64),
+                "fee_growth_outside_1": q64_to_f64(tick.fee_growth_outside_1_x64),
+                "reward_growths_outside": ({ tick.reward_growths_outside_x64 })
+                    .iter()
+                    .map(|growth| q64_to_f64(*growth))
+                    .collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn operation_state_json(operation_state: &OperationState) -> Value {
+    json!({
+        "bump": operation_state.bump,
+        "operation_owners": operation_state
+            .operation_owners
+            .iter()
+            .map(|owner| json!({
+                "key": owner.key.to_string(),
+                "roles": OPERATION_ROLES
+                    .iter()
+                    .filter(|(role, _)| owner.roles & role != 0)
+                    .map(|(_, name)| *name)
+                    .collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+        "whitelist_mints": operation_state
+            .whitelist_mints
+            .iter()
+            .map(Pubkey::to_string)
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn support_mint_json(support_mint: &SupportMintAssociated) -> Value {
+    let reviewed_extensions = (0..u64::BITS as u16)
+        .filter(|index| support_mint.reviewed_extensions & (1 << index) != 0)
+        .map(|index| match ExtensionType::try_from(index) {
+            Ok(extension) => format!("{:?}", extension),
+            Err(_) => format!("Unknown({})", index),
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "bump": support_mint.bump,
+        "mint": support_mint.mint.to_string(),
+        "reviewed_extensions": reviewed_extensions,
+    })
+}
+
+/// `OpenPositionOrIncreaseLiquidity` 转换成 `open_position_or_increase_liquidity`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, c) in name.char_indices() {
+        if c.is_ascii_uppercase() && index > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+fn fee_percent(fee_rate: u32) -> f64 {
+    f64::from(fee_rate) * 100.0 / f64::from(FEE_RATE_DENOMINATOR_VALUE)
+}
+
+fn q64_to_f64(value: u128) -> f64 {
+    value as f64 / fixed_point_64::Q64 as f64
+}
+
+/// 把 JSON 展开成 `field,value` 两列的 CSV
+pub fn to_csv(value: &Value) -> String {
+    let mut rows = vec![];
+    flatten(String::new(), value, &mut rows);
+    let mut csv = String::from("field,value\n");
+    for (field, value) in rows {
+        csv.push_str(&csv_cell(&field));
+        csv.push(',');
+        csv.push_str(&csv_cell(&value));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn flatten(prefix: String, value: &Value, rows: &mut Vec<(String, String)>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                flatten(join(key), value, rows);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(join(&index.to_string()), item, rows);
+            }
+        }
+        Value::String(string) => rows.push((prefix, string.clone())),
+        Value::Null => rows.push((prefix, String::new())),
+        value => rows.push((prefix, value.to_string())),
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pool_state_fields() {
+        let mut pool_state = PoolState {
+            mint_decimals_0: 9,
+            mint_decimals_1: 6,
+            sqrt_price_x64: fixed_point_64::Q64,
+            status: 1 << 4 | 1,
+            ..Default::default()
+        };
+        pool_state.reward_infos[1].reward_state = 2;
+        pool_state.reward_infos[1].emissions_per_second_x64 = fixed_point_64::Q64 / 2;
+        let address = Pubkey::new_unique();
+        let value = account_json(&address, &ClmmAccount::PoolState(&pool_state));
+        assert_eq!(value["type"], "PoolState");
+        assert_eq!(value["address"], address.to_string());
+        assert_eq!(value["sqrt_price_x64"], fixed_point_64::Q64.to_string());
+        // 1 个最小单位兑换 1 个最小单位,即 1 token_0 = 1000 token_1
+        assert!((value["price"].as_f64().unwrap() - 1000.0).abs() < 1e-9);
+        assert_eq!(
+            value["status"]["disabled"],
+            json!(["open_position_or_increase_liquidity", "swap"])
+        );
+        assert_eq!(value["reward_infos"][0]["state"], "Uninitialized");
+        assert_eq!(value["reward_infos"][1]["state"], "Opening");
+        assert_eq!(value["reward_infos"][1]["emissions_per_second"], 0.5);
+
+        let csv = to_csv(&value);
+        assert!(csv.starts_with("field,value\n"));
+        assert!(csv.contains("\nreward_infos.1.state,Opening\n"));
+        assert!(csv.contains("\nstatus.disabled.1,swap\n"));
+    }
+
+    #[test]
+    fn observations_in_chronological_order() {
+        let mut observation_state = ObservationState {
+            initialized: true,
+            recent_epoch: 0,
+            observation_index: 1,
+            pool_id: Pubkey::default(),
+            observations: [Observation::default(); OBSERVATION_NUM],
+            padding: [0; 4],
+        };
+        // 缓冲区已经写满一圈,索引 1 是最新的
+        for (index, observation) in observation_state.observations.iter_mut().enumerate() {
+            observation.block_timestamp = index as u32 + 1;
+        }
+        observation_state.observations[0].block_timestamp = 1000;
+        observation_state.observations[1].block_timestamp = 1001;
+        let timestamps = chronological_observations(&observation_state)
+            .iter()
+            .map(|observation| observation.block_timestamp)
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps.len(), OBSERVATION_NUM);
+        assert_eq!(timestamps[0], 3);
+        assert_eq!(timestamps[OBSERVATION_NUM - 2..], [1000, 1001]);
+
+        // 还没写满时跳过空的记录
+        observation_state.observations = [Observation::default(); OBSERVATION_NUM];
+        observation_state.observations[0].block_timestamp = 10;
+        observation_state.observations[1].block_timestamp = 20;
+        let timestamps = chronological_observations(&observation_state)
+            .iter()
+            .map(|observation| observation.block_timestamp)
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![10, 20]);
+    }
+
+    #[test]
+    fn csv_escaping() {
+        let csv = to_csv(&json!({ "a": "x,y", "b": { "c": [1, null] } }));
+        assert_eq!(csv, "field,value\na,\"x,y\"\nb.c.0,1\nb.c.1,\n");
+    }
+}
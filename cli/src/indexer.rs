@@ -0,0 +1,177 @@
+//! 把交易日志中的事件转换成 JSON lines
+//!
+//! 每行输入是一个 JSON 对象,可以是 `{"slot": .., "signature": .., "logs": [..]}`,
+//! 也可以直接是 RPC `getTransaction` 的返回值,这时从 `transaction.signatures` 和 `meta.logMessages` 中读取。
+//! 失败交易的状态会被回滚,其中的事件不会输出
+use anyhow::{anyhow, Context, Result};
+use my_clmm::client::{parse_logs, ClmmEvent};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// 一笔交易的日志
+#[derive(Debug, PartialEq, Eq)]
+pub struct TransactionLogs {
+    pub slot: u64,
+    pub signature: String,
+    pub logs: Vec<String>,
+}
+
+impl TransactionLogs {
+    /// 解析一行输入,交易失败时返回 None
+    pub fn from_json(value: &Value) -> Result<Option<Self>> {
+        let failed = value
+            .pointer("/meta/err")
+            .or_else(|| value.get("err"))
+            .map_or(false, |err| !err.is_null());
+        if failed {
+            return Ok(None);
+        }
+        let slot = value
+            .get("slot")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| anyhow!("missing slot"))?;
+        let signature = value
+            .get("signature")
+            .or_else(|| value.pointer("/transaction/signatures/0"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("missing signature"))?
+            .to_string();
+        let logs = value
+            .get("logs")
+            .or_else(|| value.pointer("/meta/logMessages"))
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("missing logs"))?
+            .iter()
+            .map(|log| {
+                log.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("log is not a string"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Some(Self {
+            slot,
+            signature,
+            logs,
+        }))
+    }
+}
+
+/// 事件的字段,Pubkey 使用 base58,超出 JSON 安全整数范围的 u128 使用字符串
+pub fn event_json(event: &ClmmEvent) -> Value {
+    match event {
+        ClmmEvent::PoolCreated(event) => json!({
+            "token_mint_0": event.token_mint_0.to_string(),
+            "token_mint_1": event.token_mint_1.to_string(),
+            "tick_spacing": event.tick_spacing,
+            "pool_state": event.pool_state.to_string(),
+            "sqrt_price_x64": event.sqrt_price_x64.to_string(),
+            "tick": event.tick,
+            "token_vault_0": event.token_vault_0.to_string(),
+            "token_vault_1": event.token_vault_1.to_string(),
+        }),
+        ClmmEvent::ConfigChange(event) => json!({
+            "index": event.index,
+            "owner": event.owner.to_string(),
+            "protocol_fee_rate": event.protocol_fee_rate,
+            "trade_fee_rate": event.trade_fee_rate,
+            "tick_spacing": event.tick_spacing,
+            "fund_fee_rate": event.fund_fee_rate,
+            "fund_owner": event.fund_owner.to_string(),
+            "status": event.status,
+        }),
+    }
+}
+
+/// 逐行读取 `input`,每个事件输出一行 JSON,返回输出的事件数量
+pub fn index(input: impl BufRead, out: &mut impl Write) -> Result<usize> {
+    let mut count = 0;
+    for (line_number, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line)
+            .with_context(|| format!("line {}: invalid JSON", line_number + 1))?;
+        let Some(transaction) = TransactionLogs::from_json(&value)
+            .with_context(|| format!("line {}", line_number + 1))?
+        else {
+            continue;
+        };
+        for (index, event) in parse_logs(&transaction.logs).iter().enumerate() {
+            let record = json!({
+                "slot": transaction.slot,
+                "signature": transaction.signature,
+                "index": index,
+                "event": event.name(),
+                "data": event_json(event),
+            });
+            writeln!(out, "{}", record)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anchor_lang::__private::base64;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Event;
+    use my_clmm::states::ConfigChangeEvent;
+
+    #[test]
+    fn index_transactions() {
+        let event = ConfigChangeEvent {
+            index: 1,
+            owner: Pubkey::new_unique(),
+            protocol_fee_rate: 120_000,
+            trade_fee_rate: 2500,
+            tick_spacing: 60,
+            fund_fee_rate: 40_000,
+            fund_owner: Pubkey::new_unique(),
+            status: 0,
+        };
+        let logs = json!([
+            format!("Program {} invoke [1]", my_clmm::id()),
+            format!("Program data: {}", base64::encode(event.data())),
+            format!("Program {} success", my_clmm::id()),
+        ]);
+        let input = [
+            json!({ "slot": 7, "signature": "sig1", "logs": logs }),
+            // getTransaction 的返回值
+            json!({
+                "slot": 8,
+                "transaction": { "signatures": ["sig2"] },
+                "meta": { "err": null, "logMessages": logs },
+            }),
+            // 失败的交易
+            json!({
+                "slot": 9,
+                "transaction": { "signatures": ["sig3"] },
+                "meta": { "err": { "InstructionError": [0, { "Custom": 1 }] }, "logMessages": logs },
+            }),
+        ]
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        let mut out = vec![];
+        assert_eq!(index(input.as_bytes(), &mut out).unwrap(), 2);
+        let records = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records[0]["slot"], 7);
+        assert_eq!(records[0]["signature"], "sig1");
+        assert_eq!(records[0]["event"], "ConfigChangeEvent");
+        assert_eq!(records[0]["data"]["owner"], event.owner.to_string());
+        assert_eq!(records[0]["data"]["trade_fee_rate"], 2500);
+        assert_eq!(records[1]["slot"], 8);
+        assert_eq!(records[1]["signature"], "sig2");
+
+        assert!(index("{\"slot\": 1}".as_bytes(), &mut vec![]).is_err());
+    }
+}
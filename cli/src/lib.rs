@@ -0,0 +1,461 @@
+//! my-clmm 的管理命令行工具
+//!
+//! 每个子命令构造一条指令,用 `--keypair` 的钱包签名后发送到 `--url`,
+//! 交易确认后打印相关账户解析后的状态。`--dry-run` 只模拟交易并打印程序日志,不会修改链上状态。
+//!
+//! [`indexer`] 把交易日志中的事件转换成 JSON lines,由 `my-clmm-indexer` 使用,
+//! [`dump`] 把账户转换成人可读的 JSON 或 CSV
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use my_clmm::client::{self, ClmmAccount};
+use my_clmm::instructions::{AmmConfigUpdate, InitializeRewardParam};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    signature::{read_keypair_file, Keypair, Signer},
+    transaction::Transaction,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub mod dump;
+pub mod indexer;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "my-clmm-cli",
+    version,
+    about = "Admin command line tool for my-clmm"
+)]
+pub struct Cli {
+    /// RPC 地址
+    #[arg(short, long, global = true, default_value = "http://127.0.0.1:8899")]
+    pub url: String,
+    /// 签名并支付手续费的钱包,默认是 `~/.config/solana/id.json`
+    #[arg(short, long, global = true)]
+    pub keypair: Option<PathBuf>,
+    /// 只模拟交易并打印日志,不发送
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+#[command(rename_all = "snake_case")]
+pub enum Command {
+    /// 创建 AMM 配置,需要 admin 签名
+    CreateAmmConfig {
+        #[arg(long)]
+        index: u16,
+        #[arg(long)]
+        tick_spacing: u16,
+        #[arg(long)]
+        trade_fee_rate: u32,
+        #[arg(long)]
+        protocol_fee_rate: u32,
+        #[arg(long)]
+        fund_fee_rate: u32,
+    },
+    /// 更新 AMM 配置的一个字段,需要 admin 签名
+    UpdateAmmConfig {
+        #[arg(long)]
+        index: u16,
+        #[command(flatten)]
+        update: AmmConfigUpdateArgs,
+    },
+    /// 更新池子状态,需要 admin 或拥有 POOL_PAUSER 角色的运营人员签名
+    UpdatePoolStatus {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        status: u8,
+    },
+    /// 创建运营账户,需要 admin 签名
+    CreateOperationAccount,
+    /// 添加或移除运营人员和白名单 mint。运营人员只能由 admin 增删,
+    /// 白名单 mint 也可以由拥有 WHITELIST_MANAGER 角色的运营人员增删
+    UpdateOperationAccount {
+        #[arg(value_enum)]
+        action: OperationAction,
+        #[arg(required = true)]
+        keys: Vec<Pubkey>,
+        /// 添加运营人员时授予的 `operation_role` 位掩码,add_operators 时必须指定并且不能为 0,其他操作忽略
+        #[arg(
+            long,
+            required_if_eq("action", "add_operators"),
+            value_parser = clap::value_parser!(u8).range(1..)
+        )]
+        roles: Option<u8>,
+    },
+    /// 转移池子中奖励的 authority,需要 admin 签名
+    TransferRewardOwner {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        new_owner: Pubkey,
+    },
+    /// 初始化池子的奖励,签名者提供奖励代币
+    InitializeReward {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        reward_mint: Pubkey,
+        #[arg(long)]
+        open_time: u64,
+        #[arg(long)]
+        end_time: u64,
+        /// 每秒的奖励数量,Q64.64 格式
+        #[arg(long)]
+        emissions_per_second_x64: u128,
+        /// 奖励代币的来源账户,默认是签名者的关联代币账户
+        #[arg(long)]
+        funder_token_account: Option<Pubkey>,
+    },
+    /// 打印账户解析后的状态
+    Show {
+        address: Pubkey,
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+}
+
+/// `show` 的输出格式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+/// `update_amm_config` 每次只能更新一个字段
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct AmmConfigUpdateArgs {
+    #[arg(long)]
+    pub trade_fee_rate: Option<u32>,
+    #[arg(long)]
+    pub protocol_fee_rate: Option<u32>,
+    #[arg(long)]
+    pub fund_fee_rate: Option<u32>,
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+    #[arg(long)]
+    pub fund_owner: Option<Pubkey>,
+}
+
+impl AmmConfigUpdateArgs {
+    pub fn update(&self) -> Result<AmmConfigUpdate> {
+        let update = match self {
+            Self {
+                trade_fee_rate: Some(rate),
+                ..
+            } => AmmConfigUpdate::TradeFeeRate(*rate),
+            Self {
+                protocol_fee_rate: Some(rate),
+                ..
+            } => AmmConfigUpdate::ProtocolFeeRate(*rate),
+            Self {
+                fund_fee_rate: Some(rate),
+                ..
+            } => AmmConfigUpdate::FundFeeRate(*rate),
+            Self {
+                owner: Some(owner), ..
+            } => AmmConfigUpdate::Owner(*owner),
+            Self {
+                fund_owner: Some(owner),
+                ..
+            } => AmmConfigUpdate::FundOwner(*owner),
+            _ => bail!("nothing to update"),
+        };
+        Ok(update)
+    }
+}
+
+/// `update_operation_account` 的 `param`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "snake_case")]
+pub enum OperationAction {
+    AddOperators,
+    RemoveOperators,
+    AddMints,
+    RemoveMints,
+}
+
+impl OperationAction {
+    pub fn param(self) -> u8 {
+        match self {
+            Self::AddOperators => 0,
+            Self::RemoveOperators => 1,
+            Self::AddMints => 2,
+            Self::RemoveMints => 3,
+        }
+    }
+}
+
+impl Command {
+    /// 构造指令,同时返回交易确认后需要打印的账户
+    pub fn instruction(&self, rpc: &RpcClient, signer: Pubkey) -> Result<(Instruction, Pubkey)> {
+        let result = match self {
+            Self::CreateAmmConfig {
+                index,
+                tick_spacing,
+                trade_fee_rate,
+                protocol_fee_rate,
+                fund_fee_rate,
+            } => (
+                client::create_amm_config(
+                    signer,
+                    *index,
+                    *tick_spacing,
+                    *trade_fee_rate,
+                    *protocol_fee_rate,
+                    *fund_fee_rate,
+                ),
+                client::amm_config_address(*index).0,
+            ),
+            Self::UpdateAmmConfig { index, update } => {
+                let amm_config = client::amm_config_address(*index).0;
+                (
+                    client::update_amm_config(signer, amm_config, update.update()?),
+                    amm_config,
+                )
+            }
+            Self::UpdatePoolStatus { pool, status } => {
+                (client::update_pool_status(signer, *pool, *status), *pool)
+            }
+            Self::CreateOperationAccount => (
+                client::create_operation_account(signer),
+                client::operation_address().0,
+            ),
+            Self::UpdateOperationAccount {
+                action,
+                keys,
+                roles,
+            } => (
+                client::update_operation_account(
+                    signer,
+                    action.param(),
+                    keys.clone(),
+                    roles.unwrap_or_default(),
+                ),
+                client::operation_address().0,
+            ),
+            Self::TransferRewardOwner { pool, new_owner } => (
+                client::transfer_reward_owner(signer, *pool, *new_owner),
+                *pool,
+            ),
+            Self::InitializeReward {
+                pool,
+                reward_mint,
+                open_time,
+                end_time,
+                emissions_per_second_x64,
+                funder_token_account,
+            } => {
+                let pool_data = rpc
+                    .get_account_data(pool)
+                    .with_context(|| format!("failed to fetch pool {}", pool))?;
+                let amm_config = client::load_zero_copy::<my_clmm::states::PoolState>(&pool_data)
+                    .map_err(|err| anyhow!("{} is not a pool: {}", pool, err))?
+                    .amm_config;
+                // 奖励代币的 token program 就是 mint 账户的 owner
+                let reward_token_program = rpc
+                    .get_account(reward_mint)
+                    .with_context(|| format!("failed to fetch mint {}", reward_mint))?
+                    .owner;
+                let funder_token_account = funder_token_account.unwrap_or_else(|| {
+                    get_associated_token_address_with_program_id(
+                        &signer,
+                        reward_mint,
+                        &reward_token_program,
+                    )
+                });
+                // 只有 mint 在链上白名单中时才传入 SupportMintAssociated
+                let with_support_mint = rpc
+                    .get_account_with_commitment(
+                        &client::support_mint_associated_address(reward_mint).0,
+                        rpc.commitment(),
+                    )?
+                    .value
+                    .is_some();
+                (
+                    client::initialize_reward(
+                        signer,
+                        funder_token_account,
+                        amm_config,
+                        *pool,
+                        *reward_mint,
+                        reward_token_program,
+                        InitializeRewardParam {
+                            open_time: *open_time,
+                            end_time: *end_time,
+                            emissions_per_second_x64: *emissions_per_second_x64,
+                        },
+                        with_support_mint,
+                    ),
+                    *pool,
+                )
+            }
+            Self::Show { .. } => bail!("show does not send a transaction"),
+        };
+        Ok(result)
+    }
+}
+
+/// 执行命令,输出写到 `out`
+pub fn run(cli: &Cli, out: &mut impl Write) -> Result<()> {
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    if let Command::Show { address, format } = &cli.command {
+        return show(&rpc, address, *format, out);
+    }
+    let payer = read_keypair(cli.keypair.as_deref())?;
+    let (instruction, account) = cli.command.instruction(&rpc, payer.pubkey())?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        rpc.get_latest_blockhash()?,
+    );
+    if cli.dry_run {
+        let result = rpc.simulate_transaction(&transaction)?.value;
+        for log in result.logs.unwrap_or_default() {
+            writeln!(out, "{}", log)?;
+        }
+        if let Some(err) = result.err {
+            bail!("simulation failed: {}", err);
+        }
+        writeln!(
+            out,
+            "simulation succeeded, {} compute units consumed",
+            result.units_consumed.unwrap_or_default()
+        )?;
+        return Ok(());
+    }
+    let signature = rpc.send_and_confirm_transaction(&transaction)?;
+    writeln!(out, "signature: {}", signature)?;
+    show(&rpc, &account, Format::Json, out)
+}
+
+/// 打印本程序账户解析后的状态,字段的格式见 [`dump`]
+pub fn show(rpc: &RpcClient, address: &Pubkey, format: Format, out: &mut impl Write) -> Result<()> {
+    let account = rpc
+        .get_account(address)
+        .with_context(|| format!("failed to fetch account {}", address))?;
+    if account.owner != my_clmm::id() {
+        bail!("{} is not owned by my-clmm", address);
+    }
+    let decoded =
+        ClmmAccount::decode(&account.data).map_err(|err| anyhow!("{}: {}", address, err))?;
+    let value = dump::account_json(address, &decoded);
+    match format {
+        Format::Json => writeln!(out, "{}", serde_json::to_string_pretty(&value)?)?,
+        Format::Csv => write!(out, "{}", dump::to_csv(&value))?,
+    }
+    Ok(())
+}
+
+fn read_keypair(path: Option<&Path>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let home = std::env::var("HOME").context("HOME is not set, use --keypair")?;
+            PathBuf::from(home).join(".config/solana/id.json")
+        }
+    };
+    read_keypair_file(&path)
+        .map_err(|err| anyhow!("failed to read keypair {}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::CommandFactory;
+    use my_clmm::states::operation_role;
+
+    #[test]
+    fn parse_commands() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from([
+            "my-clmm-cli",
+            "update_amm_config",
+            "--index",
+            "1",
+            "--trade-fee-rate",
+            "3000",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert!(cli.dry_run);
+        match cli.command {
+            Command::UpdateAmmConfig { index, update } => {
+                assert_eq!(index, 1);
+                assert_eq!(
+                    update.update().unwrap(),
+                    AmmConfigUpdate::TradeFeeRate(3000)
+                );
+            }
+            command => panic!("unexpected command {:?}", command),
+        }
+        // 每次只能更新一个字段
+        assert!(Cli::try_parse_from([
+            "my-clmm-cli",
+            "update_amm_config",
+            "--index",
+            "1",
+            "--trade-fee-rate",
+            "3000",
+            "--fund-fee-rate",
+            "1000",
+        ])
+        .is_err());
+        assert!(Cli::try_parse_from(["my-clmm-cli", "update_amm_config", "--index", "1"]).is_err());
+
+        let key = Pubkey::new_unique();
+        let cli = Cli::try_parse_from([
+            "my-clmm-cli",
+            "update_operation_account",
+            "add_operators",
+            &key.to_string(),
+            "--roles",
+            "2",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::UpdateOperationAccount {
+                action,
+                keys,
+                roles,
+            } => {
+                assert_eq!(action.param(), 0);
+                assert_eq!(keys, vec![key]);
+                assert_eq!(roles, Some(operation_role::POOL_PAUSER));
+            }
+            command => panic!("unexpected command {:?}", command),
+        }
+        // 添加运营人员必须指定非 0 的角色
+        let add_operators = |roles: &[&str]| {
+            let key = key.to_string();
+            let mut args = vec![
+                "my-clmm-cli",
+                "update_operation_account",
+                "add_operators",
+                &key,
+            ];
+            args.extend_from_slice(roles);
+            Cli::try_parse_from(args)
+        };
+        assert!(add_operators(&[]).is_err());
+        assert!(add_operators(&["--roles", "0"]).is_err());
+        assert!(Cli::try_parse_from([
+            "my-clmm-cli",
+            "update_operation_account",
+            "remove_operators",
+            &key.to_string(),
+        ])
+        .is_ok());
+    }
+}
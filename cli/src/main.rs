@@ -0,0 +1,7 @@
+use clap::Parser;
+use my_clmm_cli::Cli;
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    my_clmm_cli::run(&cli, &mut std::io::stdout())
+}
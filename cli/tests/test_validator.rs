@@ -0,0 +1,361 @@
+//! 在 solana-test-validator 上运行命令行工具
+//!
+//! 程序需要开启 `test-admin-sbf` feature 编译,测试使用和集成测试相同的 admin 签名管理指令:
+//!
+//! ```sh
+//! cargo build-sbf --manifest-path programs/my-clmm/Cargo.toml --features test-admin-sbf \
+//!     --sbf-out-dir target/deploy-test-admin
+//! SBF_OUT_DIR=$PWD/target/deploy-test-admin cargo test -p my-clmm-cli -- --ignored
+//! ```
+//!
+//! 找不到 `my_clmm.so` 时测试直接失败
+use anchor_lang::{prelude::*, AccountDeserialize};
+use anchor_spl::token::spl_token;
+use clap::Parser;
+use my_clmm::client;
+use my_clmm::libraries::{fixed_point_64, tick_math};
+use my_clmm::states::{operation_role, reward_period_limit, OperationState};
+use my_clmm_cli::Cli;
+use solana_client::rpc_client::RpcClient;
+use solana_program_test::find_file;
+use solana_sdk::{
+    account::AccountSharedData,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::Pack,
+    signature::{keypair_from_seed, write_keypair_file, Keypair, Signer},
+    system_instruction, system_program,
+    transaction::Transaction,
+};
+use solana_test_validator::TestValidatorGenesis;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 开启 test-admin 时的 admin,和 `my_clmm::admin` 中的说明一致
+fn admin() -> Keypair {
+    keypair_from_seed(&[42; 32]).unwrap()
+}
+
+/// 运行命令,返回是否成功和输出
+fn run(url: &str, args: &[&str]) -> (anyhow::Result<()>, String) {
+    let cli = Cli::try_parse_from(["my-clmm-cli", "-u", url].iter().chain(args)).unwrap();
+    let mut out = vec![];
+    let result = my_clmm_cli::run(&cli, &mut out);
+    (result, String::from_utf8(out).unwrap())
+}
+
+/// 发送交易成功后,解析输出中 `signature` 之后的账户状态
+fn run_and_show(url: &str, args: &[&str]) -> serde_json::Value {
+    let (result, out) = run(url, args);
+    result.unwrap_or_else(|err| panic!("{:?} failed: {}\n{}", args, err, out));
+    let (signature, account) = out.split_once('\n').unwrap();
+    assert!(signature.starts_with("signature: "));
+    serde_json::from_str(account).unwrap()
+}
+
+fn send(rpc: &RpcClient, instructions: &[Instruction], payer: &Keypair, signers: &[&Keypair]) {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        rpc.get_latest_blockhash().unwrap(),
+    );
+    rpc.send_and_confirm_transaction(&transaction).unwrap();
+}
+
+fn create_mint(rpc: &RpcClient, payer: &Keypair) -> Pubkey {
+    let mint = Keypair::new();
+    let space = spl_token::state::Mint::LEN;
+    send(
+        rpc,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rpc.get_minimum_balance_for_rent_exemption(space).unwrap(),
+                space as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+        ],
+        payer,
+        &[&mint],
+    );
+    mint.pubkey()
+}
+
+/// 创建 `payer` 的代币账户并铸造 `amount` 个代币
+fn create_token_account(rpc: &RpcClient, payer: &Keypair, mint: Pubkey, amount: u64) -> Pubkey {
+    let account = Keypair::new();
+    let space = spl_token::state::Account::LEN;
+    send(
+        rpc,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rpc.get_minimum_balance_for_rent_exemption(space).unwrap(),
+                space as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::ID,
+                &account.pubkey(),
+                &mint,
+                &payer.pubkey(),
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::ID,
+                &mint,
+                &account.pubkey(),
+                &payer.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        ],
+        payer,
+        &[&account],
+    );
+    account.pubkey()
+}
+
+#[test]
+#[ignore = "needs my_clmm.so built with the test-admin-sbf feature, see the module docs"]
+fn admin_commands() {
+    assert!(
+        find_file("my_clmm.so").is_some(),
+        "my_clmm.so not found, build it with the test-admin-sbf feature and set SBF_OUT_DIR"
+    );
+    let admin = admin();
+    let operator = Keypair::new();
+    let mut genesis = TestValidatorGenesis::default();
+    genesis.add_program("my_clmm", my_clmm::id());
+    for signer in [&admin, &operator] {
+        genesis.add_account(
+            signer.pubkey(),
+            AccountSharedData::new(100_000_000_000, 0, &system_program::ID),
+        );
+    }
+    let (validator, _) = genesis.start();
+    let url = validator.rpc_url();
+    let url = url.as_str();
+    let rpc = RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed());
+    let keypair_path = |signer: &Keypair| {
+        let path = std::env::temp_dir().join(format!("{}.json", signer.pubkey()));
+        write_keypair_file(signer, &path).unwrap();
+        path
+    };
+    let admin_path = keypair_path(&admin);
+    let operator_path = keypair_path(&operator);
+    let admin_keypair = admin_path.to_str().unwrap();
+    let operator_keypair = operator_path.to_str().unwrap();
+
+    let amm_config = client::amm_config_address(0).0;
+    let output = run_and_show(
+        url,
+        &[
+            "-k",
+            admin_keypair,
+            "create_amm_config",
+            "--index",
+            "0",
+            "--tick-spacing",
+            "60",
+            "--trade-fee-rate",
+            "2500",
+            "--protocol-fee-rate",
+            "120000",
+            "--fund-fee-rate",
+            "40000",
+        ],
+    );
+    assert_eq!(output["type"], "AmmConfig");
+    assert_eq!(output["address"], amm_config.to_string());
+    assert_eq!(output["tick_spacing"], 60);
+
+    let (result, out) = run(url, &["show", &amm_config.to_string()]);
+    result.unwrap();
+    let output: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(output["trade_fee_rate"], 2500);
+
+    // 不是 admin,模拟失败并打印程序日志
+    let (result, out) = run(
+        url,
+        &[
+            "-k",
+            operator_keypair,
+            "--dry-run",
+            "update_amm_config",
+            "--index",
+            "0",
+            "--trade-fee-rate",
+            "3000",
+        ],
+    );
+    assert!(result.is_err());
+    assert!(out.contains("NotApproved"));
+
+    // 模拟不会修改账户
+    let (result, out) = run(url, &["show", &amm_config.to_string(), "--format", "csv"]);
+    result.unwrap();
+    assert!(out.contains("\ntrade_fee_rate,2500\n"));
+
+    // admin 创建运营账户,operator 可以增删白名单 mint 和更新池子状态
+    let operation = client::operation_address().0;
+    run_and_show(url, &["-k", admin_keypair, "create_operation_account"]);
+    let roles = (operation_role::WHITELIST_MANAGER | operation_role::POOL_PAUSER).to_string();
+    let output = run_and_show(
+        url,
+        &[
+            "-k",
+            admin_keypair,
+            "update_operation_account",
+            "add_operators",
+            &operator.pubkey().to_string(),
+            "--roles",
+            &roles,
+        ],
+    );
+    assert_eq!(output["type"], "OperationState");
+
+    // 白名单管理员添加 mint,账户扩容后链上状态包含新的 mint
+    let mint = Pubkey::new_unique();
+    run_and_show(
+        url,
+        &[
+            "-k",
+            operator_keypair,
+            "update_operation_account",
+            "add_mints",
+            &mint.to_string(),
+        ],
+    );
+    let account = rpc.get_account(&operation).unwrap();
+    assert_eq!(account.data.len(), OperationState::space(1, 1));
+    let operation_state = OperationState::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(operation_state.whitelist_mints, vec![mint]);
+    assert_eq!(operation_state.operation_owners[0].key, operator.pubkey());
+
+    // 命令行工具不能创建池子,由 admin 直接发送交易创建
+    let mint_a = create_mint(&rpc, &admin);
+    let mint_b = create_mint(&rpc, &admin);
+    let (token_mint_0, token_mint_1) = if mint_a < mint_b {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    };
+    send(
+        &rpc,
+        &[client::create_pool(
+            admin.pubkey(),
+            amm_config,
+            token_mint_0,
+            spl_token::ID,
+            token_mint_1,
+            spl_token::ID,
+            tick_math::get_sqrt_price_at_tick(0).unwrap(),
+            0,
+            client::CreatePoolOptions::default(),
+        )],
+        &admin,
+        &[],
+    );
+    let pool = client::pool_address(&amm_config, &token_mint_0, &token_mint_1)
+        .0
+        .to_string();
+
+    // 拥有 POOL_PAUSER 角色的运营人员禁止 swap
+    let output = run_and_show(
+        url,
+        &[
+            "-k",
+            operator_keypair,
+            "update_pool_status",
+            "--pool",
+            &pool,
+            "--status",
+            "16",
+        ],
+    );
+    assert_eq!(output["status"]["raw"], 16);
+    assert_eq!(output["status"]["disabled"], serde_json::json!(["swap"]));
+
+    // 池子的创建者用 token_mint_0 初始化第一个奖励,每秒发放一个代币
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let open_time = now + 600;
+    let end_time = open_time + reward_period_limit::MIN_REWARD_PERIOD;
+    let funder_token_account = create_token_account(
+        &rpc,
+        &admin,
+        token_mint_0,
+        reward_period_limit::MIN_REWARD_PERIOD,
+    );
+    let output = run_and_show(
+        url,
+        &[
+            "-k",
+            admin_keypair,
+            "initialize_reward",
+            "--pool",
+            &pool,
+            "--reward-mint",
+            &token_mint_0.to_string(),
+            "--open-time",
+            &open_time.to_string(),
+            "--end-time",
+            &end_time.to_string(),
+            "--emissions-per-second-x64",
+            &fixed_point_64::Q64.to_string(),
+            "--funder-token-account",
+            &funder_token_account.to_string(),
+        ],
+    );
+    let reward_info = &output["reward_infos"][0];
+    assert_eq!(reward_info["token_mint"], token_mint_0.to_string());
+    assert_eq!(reward_info["open_time"], open_time);
+    assert_eq!(reward_info["authority"], admin.pubkey().to_string());
+    let reward_vault: Pubkey = reward_info["token_vault"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(
+        rpc.get_token_account_balance(&reward_vault).unwrap().amount,
+        reward_period_limit::MIN_REWARD_PERIOD.to_string()
+    );
+
+    // admin 把奖励的 authority 转给 operator
+    let output = run_and_show(
+        url,
+        &[
+            "-k",
+            admin_keypair,
+            "transfer_reward_owner",
+            "--pool",
+            &pool,
+            "--new-owner",
+            &operator.pubkey().to_string(),
+        ],
+    );
+    assert_eq!(
+        output["reward_infos"][0]["authority"],
+        operator.pubkey().to_string()
+    );
+    assert_eq!(output["owner"], operator.pubkey().to_string());
+
+    std::fs::remove_file(admin_path).unwrap();
+    std::fs::remove_file(operator_path).unwrap();
+}
@@ -0,0 +1,430 @@
+//! 在 solana-program-test 中原生运行 my-clmm 的集成测试
+//!
+//! 程序开启了 `test-admin` feature,admin 的私钥由 [`admin`] 生成,所以 admin 才能执行的指令也可以测试。
+//! 原生运行不需要先编译出 SBF 程序:
+//!
+//! ```sh
+//! cargo test -p my-clmm-tests
+//! ```
+//!
+//! spl-token 和 Token-2022 使用 solana-program-test 自带的程序,带扩展的 mint 在本地创建
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{entrypoint::ProgramResult, system_instruction};
+use anchor_lang::{system_program, AccountDeserialize, ZeroCopy};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+};
+use my_clmm::client;
+use my_clmm::libraries::tick_math;
+use solana_program_test::{find_file, processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    signature::{keypair_from_seed, Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+/// 生成测试 admin 私钥的种子,对应 `test-admin` feature 下的 `my_clmm::admin::id()`
+pub const ADMIN_SEED: [u8; 32] = [42; 32];
+
+/// 每个测试账户初始的 lamports
+pub const INITIAL_LAMPORTS: u64 = 100_000_000_000;
+
+/// 给测试账户铸造的代币数量
+pub const INITIAL_TOKEN_AMOUNT: u64 = 1_000_000_000_000;
+
+/// 交易的执行结果,失败时是交易的错误
+pub type TransactionResult = std::result::Result<(), TransactionError>;
+
+/// 测试使用的 admin
+pub fn admin() -> Keypair {
+    keypair_from_seed(&ADMIN_SEED).unwrap()
+}
+
+/// anchor 生成的 `entry` 要求账户切片和 `AccountInfo` 的生命周期相同,`processor!` 给不出这样的切片,
+/// 所以把账户列表复制一份并泄漏掉
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    my_clmm::entry(program_id, accounts, data)
+}
+
+/// 本地创建 Token-2022 mint 时初始化的扩展
+#[derive(Clone, Copy, Debug)]
+pub enum MintExtension {
+    /// 转账手续费,`basis_points` 的单位是万分之一
+    TransferFee { basis_points: u16, maximum_fee: u64 },
+    /// 永久代理,程序默认不支持这个扩展
+    PermanentDelegate,
+}
+
+impl MintExtension {
+    fn extension_type(&self) -> ExtensionType {
+        match self {
+            Self::TransferFee { .. } => ExtensionType::TransferFeeConfig,
+            Self::PermanentDelegate => ExtensionType::PermanentDelegate,
+        }
+    }
+}
+
+/// 断言交易因为 `error` 失败,交易中只能有一条指令
+pub fn assert_error(result: TransactionResult, error: impl Into<u32>) {
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error.into()))
+    );
+}
+
+/// 按地址排序,池子要求 token_mint_0 小于 token_mint_1
+pub fn sort_mints(mint_a: Pubkey, mint_b: Pubkey) -> (Pubkey, Pubkey) {
+    if mint_a < mint_b {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    }
+}
+
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+}
+
+impl TestEnv {
+    /// 启动 solana-program-test,admin 已经有足够的 lamports
+    pub async fn start() -> Self {
+        let mut program_test = ProgramTest::default();
+        // 设置了 SBF_OUT_DIR 时也不能加载没有开启 test-admin 的 my_clmm.so
+        program_test.prefer_bpf(false);
+        program_test.add_program("my_clmm", my_clmm::id(), processor!(process_instruction));
+        program_test.add_account(
+            admin().pubkey(),
+            Account::new(INITIAL_LAMPORTS, 0, &system_program::ID),
+        );
+        Self {
+            context: program_test.start_with_context().await,
+        }
+    }
+
+    /// 用编译出的 SBF 程序启动 solana-program-test,只在需要真实的 SBF 运行环境(例如 32KB 的堆)时使用。
+    /// 程序需要开启 `test-admin-sbf` feature 编译到单独的目录,并用 `SBF_OUT_DIR` 指向这个目录:
+    ///
+    /// ```sh
+    /// cargo build-sbf --manifest-path programs/my-clmm/Cargo.toml --features test-admin-sbf \
+    ///     --sbf-out-dir target/deploy-test-admin
+    /// SBF_OUT_DIR=$PWD/target/deploy-test-admin cargo test -p my-clmm-tests -- --ignored
+    /// ```
+    ///
+    /// 找不到 `my_clmm.so` 时直接失败
+    pub async fn start_sbf() -> Self {
+        assert!(
+            find_file("my_clmm.so").is_some(),
+            "my_clmm.so not found, build it with the test-admin-sbf feature and set SBF_OUT_DIR"
+        );
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(true);
+        program_test.add_program("my_clmm", my_clmm::id(), None);
+        program_test.add_account(
+            admin().pubkey(),
+            Account::new(INITIAL_LAMPORTS, 0, &system_program::ID),
+        );
+        Self {
+            context: program_test.start_with_context().await,
+        }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// 由 payer 支付手续费并发送交易
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> TransactionResult {
+        // 相同的交易会被当作重复交易拒绝,每次都使用新的 blockhash
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())
+    }
+
+    /// 创建一个有足够 lamports 的新账户
+    pub async fn new_user(&mut self) -> Keypair {
+        let user = Keypair::new();
+        self.process(
+            &[system_instruction::transfer(
+                &self.payer(),
+                &user.pubkey(),
+                INITIAL_LAMPORTS,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        user
+    }
+
+    pub async fn now(&mut self) -> u64 {
+        let clock = self
+            .context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap();
+        clock.unix_timestamp as u64
+    }
+
+    pub async fn get_account(&mut self, address: Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn anchor_account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.get_account(address).await.unwrap();
+        T::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    pub async fn zero_copy_account<T: ZeroCopy>(&mut self, address: Pubkey) -> T {
+        let account = self.get_account(address).await.unwrap();
+        *client::load_zero_copy::<T>(&account.data).unwrap()
+    }
+
+    /// 创建 spl-token mint,mint authority 是 payer
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let space = spl_token::state::Mint::LEN;
+        let instructions = [
+            system_instruction::create_account(
+                &self.payer(),
+                &mint.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &self.payer(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ];
+        self.process(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// 创建带有 `extensions` 的 Token-2022 mint,mint authority 和所有扩展的 authority 都是 payer
+    pub async fn create_mint_2022(&mut self, decimals: u8, extensions: &[MintExtension]) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let extension_types: Vec<ExtensionType> =
+            extensions.iter().map(|e| e.extension_type()).collect();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(
+            &extension_types,
+        )
+        .unwrap();
+        let mut instructions = vec![system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        )];
+        // 扩展必须在 initialize_mint 之前初始化
+        for extension in extensions {
+            instructions.push(match *extension {
+                MintExtension::TransferFee {
+                    basis_points,
+                    maximum_fee,
+                } => transfer_fee::instruction::initialize_transfer_fee_config(
+                    &spl_token_2022::id(),
+                    &mint.pubkey(),
+                    Some(&payer),
+                    Some(&payer),
+                    basis_points,
+                    maximum_fee,
+                )
+                .unwrap(),
+                MintExtension::PermanentDelegate => {
+                    spl_token_2022::instruction::initialize_permanent_delegate(
+                        &spl_token_2022::id(),
+                        &mint.pubkey(),
+                        &payer,
+                    )
+                    .unwrap()
+                }
+            });
+        }
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::id(),
+                &mint.pubkey(),
+                &payer,
+                None,
+                decimals,
+            )
+            .unwrap(),
+        );
+        self.process(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// mint 的 owner,也就是它所属的 token program
+    pub async fn token_program_of(&mut self, mint: Pubkey) -> Pubkey {
+        self.get_account(mint).await.unwrap().owner
+    }
+
+    /// 给 `owner` 创建 `mint` 的代币账户,并铸造 [`INITIAL_TOKEN_AMOUNT`] 个代币
+    pub async fn create_token_account(&mut self, mint: Pubkey, owner: Pubkey) -> Pubkey {
+        let token_program = self.token_program_of(mint).await;
+        let space = if token_program == spl_token_2022::id() {
+            // 代币账户需要带上 mint 的扩展所要求的扩展,例如 TransferFeeAmount
+            let mint_account = self.get_account(mint).await.unwrap();
+            let mint_state =
+                StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.data)
+                    .unwrap();
+            let required = ExtensionType::get_required_init_account_extensions(
+                &mint_state.get_extension_types().unwrap(),
+            );
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&required)
+                .unwrap()
+        } else {
+            spl_token::state::Account::LEN
+        };
+        let account = Keypair::new();
+        let payer = self.payer();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &payer,
+                &account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &token_program,
+            ),
+            spl_token_2022::instruction::initialize_account3(
+                &token_program,
+                &account.pubkey(),
+                &mint,
+                &owner,
+            )
+            .unwrap(),
+            spl_token_2022::instruction::mint_to(
+                &token_program,
+                &mint,
+                &account.pubkey(),
+                &payer,
+                &[],
+                INITIAL_TOKEN_AMOUNT,
+            )
+            .unwrap(),
+        ];
+        self.process(&instructions, &[&account]).await.unwrap();
+        account.pubkey()
+    }
+
+    /// 代币账户的余额,spl-token 和 Token-2022 的账户都可以读取
+    pub async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.get_account(address).await.unwrap();
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    /// 由 admin 创建 AMM 配置
+    pub async fn create_amm_config(&mut self, index: u16) -> Pubkey {
+        let admin = admin();
+        self.process(
+            &[client::create_amm_config(
+                admin.pubkey(),
+                index,
+                60,
+                2500,
+                120_000,
+                40_000,
+            )],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+        client::amm_config_address(index).0
+    }
+
+    /// 由 admin 创建运营账户
+    pub async fn create_operation_account(&mut self) -> Pubkey {
+        let admin = admin();
+        self.process(
+            &[client::create_operation_account(admin.pubkey())],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+        client::operation_address().0
+    }
+
+    /// 由 admin 给 `keys` 授予 `roles` 角色
+    pub async fn add_operators(&mut self, keys: Vec<Pubkey>, roles: u8) {
+        let admin = admin();
+        self.process(
+            &[client::update_operation_account(
+                admin.pubkey(),
+                0,
+                keys,
+                roles,
+            )],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// 用 `mint_a` 和 `mint_b` 以价格 1 创建池子,返回池子地址
+    pub async fn create_pool(
+        &mut self,
+        creator: &Keypair,
+        amm_config: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        options: client::CreatePoolOptions,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        let (token_mint_0, token_mint_1) = sort_mints(mint_a, mint_b);
+        let token_program_0 = self.token_program_of(token_mint_0).await;
+        let token_program_1 = self.token_program_of(token_mint_1).await;
+        let instruction = client::create_pool(
+            creator.pubkey(),
+            amm_config,
+            token_mint_0,
+            token_program_0,
+            token_mint_1,
+            token_program_1,
+            tick_math::get_sqrt_price_at_tick(0).unwrap(),
+            0,
+            options,
+        );
+        self.process(&[instruction], &[creator]).await?;
+        Ok(client::pool_address(&amm_config, &token_mint_0, &token_mint_1).0)
+    }
+}
@@ -0,0 +1,316 @@
+//! AMM 配置以及创建池子的权限
+use anchor_lang::prelude::*;
+use my_clmm::client;
+use my_clmm::error::ErrorCode;
+use my_clmm::instructions::AmmConfigUpdate;
+use my_clmm::states::*;
+use my_clmm_tests::*;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn create_amm_config() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let stranger = env.new_user().await;
+
+    let result = env
+        .process(
+            &[client::create_amm_config(
+                stranger.pubkey(),
+                0,
+                60,
+                2500,
+                120_000,
+                40_000,
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    // 费率超过分母
+    let result = env
+        .process(
+            &[client::create_amm_config(
+                admin.pubkey(),
+                0,
+                60,
+                FEE_RATE_DENOMINATOR_VALUE,
+                0,
+                0,
+            )],
+            &[&admin],
+        )
+        .await;
+    assert_error(result, ErrorCode::InvalidTradeFeeRate);
+
+    let amm_config = env.create_amm_config(0).await;
+    let config: AmmConfig = env.anchor_account(amm_config).await;
+    assert_eq!(config.index, 0);
+    assert_eq!(config.owner, admin.pubkey());
+    assert_eq!(config.fund_owner, admin.pubkey());
+    assert_eq!(config.tick_spacing, 60);
+    assert_eq!(config.trade_fee_rate, 2500);
+    assert_eq!(config.protocol_fee_rate, 120_000);
+    assert_eq!(config.fund_fee_rate, 40_000);
+    assert_eq!(config.status, 0);
+}
+
+#[tokio::test]
+async fn update_amm_config() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let stranger = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+
+    let result = env
+        .process(
+            &[client::update_amm_config(
+                stranger.pubkey(),
+                amm_config,
+                AmmConfigUpdate::TradeFeeRate(3000),
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    // 协议费率和基金费率之和不能超过分母
+    let result = env
+        .process(
+            &[client::update_amm_config(
+                admin.pubkey(),
+                amm_config,
+                AmmConfigUpdate::ProtocolFeeRate(FEE_RATE_DENOMINATOR_VALUE),
+            )],
+            &[&admin],
+        )
+        .await;
+    assert_error(result, ErrorCode::InvalidFeeRateSum);
+
+    let fund_owner = Pubkey::new_unique();
+    for update in [
+        AmmConfigUpdate::TradeFeeRate(3000),
+        AmmConfigUpdate::ProtocolFeeRate(100_000),
+        AmmConfigUpdate::FundFeeRate(50_000),
+        AmmConfigUpdate::Owner(stranger.pubkey()),
+        AmmConfigUpdate::FundOwner(fund_owner),
+    ] {
+        env.process(
+            &[client::update_amm_config(
+                admin.pubkey(),
+                amm_config,
+                update,
+            )],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+    }
+    let config: AmmConfig = env.anchor_account(amm_config).await;
+    assert_eq!(config.trade_fee_rate, 3000);
+    assert_eq!(config.protocol_fee_rate, 100_000);
+    assert_eq!(config.fund_fee_rate, 50_000);
+    assert_eq!(config.owner, stranger.pubkey());
+    assert_eq!(config.fund_owner, fund_owner);
+
+    // 配置的 owner 不是 admin,仍然不能更新配置
+    let result = env
+        .process(
+            &[client::update_amm_config(
+                stranger.pubkey(),
+                amm_config,
+                AmmConfigUpdate::TradeFeeRate(2500),
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+}
+
+#[tokio::test]
+async fn update_amm_config_status() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let config_owner = env.new_user().await;
+    let stranger = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+    env.process(
+        &[client::update_amm_config(
+            admin.pubkey(),
+            amm_config,
+            AmmConfigUpdate::Owner(config_owner.pubkey()),
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let mint_a = env.create_mint(6).await;
+    let mint_b = env.create_mint(6).await;
+
+    let result = env
+        .process(
+            &[client::update_amm_config_status(
+                stranger.pubkey(),
+                amm_config,
+                1,
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    // 配置的 owner 可以禁止创建池子
+    env.process(
+        &[client::update_amm_config_status(
+            config_owner.pubkey(),
+            amm_config,
+            1 << AmmConfigStatusBitIndex::CreatePool as u8,
+        )],
+        &[&config_owner],
+    )
+    .await
+    .unwrap();
+    let result = env
+        .create_pool(
+            &stranger,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .map(|_| ());
+    assert_error(result, ErrorCode::AmmConfigDisabled);
+
+    // admin 也可以更新状态
+    env.process(
+        &[client::update_amm_config_status(
+            admin.pubkey(),
+            amm_config,
+            0,
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let config: AmmConfig = env.anchor_account(amm_config).await;
+    assert_eq!(config.status, 0);
+    env.create_pool(
+        &stranger,
+        amm_config,
+        mint_a,
+        mint_b,
+        client::CreatePoolOptions::default(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn pool_creator_permission() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let creator = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+    env.process(
+        &[client::update_amm_config_status(
+            admin.pubkey(),
+            amm_config,
+            1 << AmmConfigStatusBitIndex::PermissionedCreatePool as u8,
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let mint_a = env.create_mint(6).await;
+    let mint_b = env.create_mint(6).await;
+    let mint_c = env.create_mint(9).await;
+    let with_permission = client::CreatePoolOptions {
+        with_creator_permission: true,
+        ..Default::default()
+    };
+
+    let result = env
+        .create_pool(
+            &creator,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .map(|_| ());
+    assert_error(result, ErrorCode::NotApproved);
+
+    // 只有 admin 可以授权
+    let result = env
+        .process(
+            &[client::create_pool_creator_permission(
+                creator.pubkey(),
+                amm_config,
+                creator.pubkey(),
+            )],
+            &[&creator],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    env.process(
+        &[client::create_pool_creator_permission(
+            admin.pubkey(),
+            amm_config,
+            creator.pubkey(),
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let permission_address =
+        client::pool_creator_permission_address(&amm_config, &creator.pubkey()).0;
+    let permission: PoolCreatorPermission = env.anchor_account(permission_address).await;
+    assert_eq!(permission.amm_config, amm_config);
+    assert_eq!(permission.creator, creator.pubkey());
+    let pool_state = env
+        .create_pool(&creator, amm_config, mint_a, mint_b, with_permission)
+        .await
+        .unwrap();
+    let pool: PoolState = env.zero_copy_account(pool_state).await;
+    assert_eq!({ pool.owner }, creator.pubkey());
+
+    // 只有 admin 可以撤销授权
+    let result = env
+        .process(
+            &[client::close_pool_creator_permission(
+                creator.pubkey(),
+                amm_config,
+                creator.pubkey(),
+            )],
+            &[&creator],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    env.process(
+        &[client::close_pool_creator_permission(
+            admin.pubkey(),
+            amm_config,
+            creator.pubkey(),
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    assert!(env.get_account(permission_address).await.is_none());
+    // 撤销之后不能再创建新池子,已经创建的池子不受影响
+    let result = env
+        .create_pool(&creator, amm_config, mint_a, mint_c, with_permission)
+        .await
+        .map(|_| ());
+    assert_error(
+        result,
+        anchor_lang::error::ErrorCode::AccountNotInitialized as u32,
+    );
+    assert!(env.get_account(pool_state).await.is_some());
+}
@@ -0,0 +1,502 @@
+//! 运营账户,以及 admin 和运营人员才能执行的指令
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use my_clmm::client;
+use my_clmm::error::ErrorCode;
+use my_clmm::states::*;
+use my_clmm_tests::*;
+use solana_program_test::tokio;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    signature::Signer,
+};
+
+#[tokio::test]
+async fn create_operation_account() {
+    let mut env = TestEnv::start().await;
+    let stranger = env.new_user().await;
+
+    let result = env
+        .process(
+            &[client::create_operation_account(stranger.pubkey())],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    let operation_state = env.create_operation_account().await;
+    let operation: OperationState = env.anchor_account(operation_state).await;
+    assert_eq!(operation.bump, client::operation_address().1);
+    assert!(operation.operation_owners.is_empty());
+    assert!(operation.whitelist_mints.is_empty());
+}
+
+#[tokio::test]
+async fn update_operation_account() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let whitelist_manager = env.new_user().await;
+    let stranger = env.new_user().await;
+    let operation_state = env.create_operation_account().await;
+
+    // 只有 admin 可以增删运营人员
+    let result = env
+        .process(
+            &[client::update_operation_account(
+                stranger.pubkey(),
+                0,
+                vec![stranger.pubkey()],
+                operation_role::ALL,
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    let result = env
+        .process(
+            &[client::update_operation_account(
+                admin.pubkey(),
+                4,
+                vec![],
+                0,
+            )],
+            &[&admin],
+        )
+        .await;
+    assert_error(result, ErrorCode::InvalidUpdateConfigFlag);
+
+    env.add_operators(
+        vec![whitelist_manager.pubkey()],
+        operation_role::WHITELIST_MANAGER,
+    )
+    .await;
+    let operation: OperationState = env.anchor_account(operation_state).await;
+    assert_eq!(
+        operation.operation_owners,
+        vec![OperationOwner {
+            key: whitelist_manager.pubkey(),
+            roles: operation_role::WHITELIST_MANAGER,
+        }]
+    );
+
+    // 白名单管理员可以管理白名单,但不能增删运营人员
+    let result = env
+        .process(
+            &[client::update_operation_account(
+                whitelist_manager.pubkey(),
+                0,
+                vec![stranger.pubkey()],
+                operation_role::ALL,
+            )],
+            &[&whitelist_manager],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+    let mints: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    env.process(
+        &[client::update_operation_account(
+            whitelist_manager.pubkey(),
+            2,
+            mints.clone(),
+            0,
+        )],
+        &[&whitelist_manager],
+    )
+    .await
+    .unwrap();
+    // 扩容后账户的大小正好容纳所有数据
+    let account = env.get_account(operation_state).await.unwrap();
+    assert_eq!(account.data.len(), OperationState::space(1, 3));
+    env.process(
+        &[client::update_operation_account(
+            whitelist_manager.pubkey(),
+            3,
+            vec![mints[1]],
+            0,
+        )],
+        &[&whitelist_manager],
+    )
+    .await
+    .unwrap();
+    let operation: OperationState = env.anchor_account(operation_state).await;
+    assert_eq!(operation.whitelist_mints, vec![mints[0], mints[2]]);
+
+    let result = env
+        .process(
+            &[client::update_operation_account(
+                stranger.pubkey(),
+                2,
+                vec![Pubkey::new_unique()],
+                0,
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    env.process(
+        &[client::update_operation_account(
+            admin.pubkey(),
+            1,
+            vec![whitelist_manager.pubkey()],
+            0,
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let operation: OperationState = env.anchor_account(operation_state).await;
+    assert!(operation.operation_owners.is_empty());
+    let result = env
+        .process(
+            &[client::update_operation_account(
+                whitelist_manager.pubkey(),
+                3,
+                vec![mints[0]],
+                0,
+            )],
+            &[&whitelist_manager],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+}
+
+/// 运营账户按 Borsh 反序列化到 SBF 程序 32KB 的 bump 堆上,分配的内存不会释放。
+/// 在 SBF 程序上把白名单 mint 和运营人员加到上限,检查每一步都不会耗尽堆内存
+#[tokio::test]
+#[ignore = "needs my_clmm.so built with the test-admin-sbf feature, see TestEnv::start_sbf"]
+async fn operation_account_capacity_on_sbf() {
+    let mut env = TestEnv::start_sbf().await;
+    let admin = admin();
+    let operation_state = env.create_operation_account().await;
+    // 一笔交易放得下的 Pubkey 数量有限,分批添加
+    const BATCH: usize = 20;
+
+    let mints: Vec<Pubkey> = (0..MAX_WHITELIST_MINT_NUM)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for batch in mints.chunks(BATCH) {
+        env.process(
+            &[client::update_operation_account(
+                admin.pubkey(),
+                2,
+                batch.to_vec(),
+                0,
+            )],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+    }
+    // 白名单已经满了,再添加运营人员时反序列化的数据最多
+    let operators: Vec<Pubkey> = (0..MAX_OPERATION_OWNER_NUM)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for batch in operators.chunks(BATCH) {
+        env.add_operators(batch.to_vec(), operation_role::ALL).await;
+    }
+    let account = env.get_account(operation_state).await.unwrap();
+    assert_eq!(
+        account.data.len(),
+        OperationState::space(MAX_OPERATION_OWNER_NUM, MAX_WHITELIST_MINT_NUM)
+    );
+
+    // 都满了之后返回 OperationCapacityExhausted
+    let result = env
+        .process(
+            &[client::update_operation_account(
+                admin.pubkey(),
+                2,
+                vec![Pubkey::new_unique()],
+                0,
+            )],
+            &[&admin],
+        )
+        .await;
+    assert_error(result, ErrorCode::OperationCapacityExhausted);
+    let result = env
+        .process(
+            &[client::update_operation_account(
+                admin.pubkey(),
+                0,
+                vec![Pubkey::new_unique()],
+                operation_role::ALL,
+            )],
+            &[&admin],
+        )
+        .await;
+    assert_error(result, ErrorCode::OperationCapacityExhausted);
+
+    // 运营人员满了之后仍然可以替换白名单 mint
+    let mint = Pubkey::new_unique();
+    env.process(
+        &[
+            client::update_operation_account(admin.pubkey(), 3, vec![mints[0]], 0),
+            client::update_operation_account(admin.pubkey(), 2, vec![mint], 0),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let operation: OperationState = env.anchor_account(operation_state).await;
+    assert_eq!(operation.operation_owners.len(), MAX_OPERATION_OWNER_NUM);
+    assert_eq!(operation.whitelist_mints.len(), MAX_WHITELIST_MINT_NUM);
+    assert_eq!(operation.whitelist_mints.last(), Some(&mint));
+}
+
+#[tokio::test]
+async fn migrate_operation_account() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let stranger = env.new_user().await;
+    let operator = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    // 旧版本 zero_copy 布局: bump, [Pubkey; 10] 运营人员, [Pubkey; 100] 白名单 mint
+    let legacy = client::legacy_operation_address();
+    let mut data = OperationState::DISCRIMINATOR.to_vec();
+    data.push(legacy.1);
+    data.resize(LEGACY_OPERATION_LEN, 0);
+    data[9..41].copy_from_slice(operator.as_ref());
+    data[329..361].copy_from_slice(mint.as_ref());
+    let lamports = Rent::default().minimum_balance(data.len());
+    env.context.set_account(
+        &legacy.0,
+        &AccountSharedData::from(Account {
+            lamports,
+            data,
+            owner: my_clmm::id(),
+            executable: false,
+            rent_epoch: 0,
+        }),
+    );
+
+    let result = env
+        .process(
+            &[client::migrate_operation_account(stranger.pubkey())],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    let admin_lamports = env.get_account(admin.pubkey()).await.unwrap().lamports;
+    env.process(
+        &[client::migrate_operation_account(admin.pubkey())],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let operation_state = client::operation_address().0;
+    let account = env.get_account(operation_state).await.unwrap();
+    assert_eq!(account.data.len(), OperationState::space(1, 1));
+    let operation: OperationState = env.anchor_account(operation_state).await;
+    assert_eq!(operation.bump, client::operation_address().1);
+    assert_eq!(
+        operation.operation_owners,
+        vec![OperationOwner {
+            key: operator,
+            roles: operation_role::ALL,
+        }]
+    );
+    assert_eq!(operation.whitelist_mints, vec![mint]);
+    // 旧账户已经关闭,租金退给 admin
+    assert!(env.get_account(legacy.0).await.is_none());
+    let new_rent = account.lamports;
+    assert_eq!(
+        env.get_account(admin.pubkey()).await.unwrap().lamports,
+        admin_lamports + lamports - new_rent
+    );
+}
+
+#[tokio::test]
+async fn update_pool_status() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let pauser = env.new_user().await;
+    let reward_manager = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+    let mint_a = env.create_mint(6).await;
+    let mint_b = env.create_mint(6).await;
+    let pool_state = env
+        .create_pool(
+            &reward_manager,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // admin 不需要运营账户,运营账户还没有创建时也可以暂停池子
+    env.process(
+        &[client::update_pool_status(
+            admin.pubkey(),
+            pool_state,
+            1 << 4,
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let pool: PoolState = env.zero_copy_account(pool_state).await;
+    assert_eq!({ pool.status }, 1 << 4);
+
+    env.create_operation_account().await;
+    env.add_operators(vec![pauser.pubkey()], operation_role::POOL_PAUSER)
+        .await;
+    env.add_operators(
+        vec![reward_manager.pubkey()],
+        operation_role::REWARD_MANAGER,
+    )
+    .await;
+
+    // 没有 POOL_PAUSER 角色的运营人员不能更新池子状态,即使他是池子的创建者
+    let result = env
+        .process(
+            &[client::update_pool_status(
+                reward_manager.pubkey(),
+                pool_state,
+                1,
+            )],
+            &[&reward_manager],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    env.process(
+        &[client::update_pool_status(
+            pauser.pubkey(),
+            pool_state,
+            0b11,
+        )],
+        &[&pauser],
+    )
+    .await
+    .unwrap();
+    let pool: PoolState = env.zero_copy_account(pool_state).await;
+    assert_eq!({ pool.status }, 0b11);
+
+    env.process(
+        &[client::update_pool_status(admin.pubkey(), pool_state, 0)],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let pool: PoolState = env.zero_copy_account(pool_state).await;
+    assert_eq!({ pool.status }, 0);
+}
+
+#[tokio::test]
+async fn transfer_reward_owner() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let creator = env.new_user().await;
+    let new_owner = Pubkey::new_unique();
+    let amm_config = env.create_amm_config(0).await;
+    let mint_a = env.create_mint(6).await;
+    let mint_b = env.create_mint(6).await;
+    let pool_state = env
+        .create_pool(
+            &creator,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // 池子的创建者也不能转移
+    let result = env
+        .process(
+            &[client::transfer_reward_owner(
+                creator.pubkey(),
+                pool_state,
+                new_owner,
+            )],
+            &[&creator],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    env.process(
+        &[client::transfer_reward_owner(
+            admin.pubkey(),
+            pool_state,
+            new_owner,
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let pool: PoolState = env.zero_copy_account(pool_state).await;
+    assert_eq!({ pool.owner }, new_owner);
+    for reward_info in pool.reward_infos {
+        assert_eq!({ reward_info.authority }, new_owner);
+    }
+}
+
+#[tokio::test]
+async fn support_mint_associated() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let whitelist_manager = env.new_user().await;
+    let stranger = env.new_user().await;
+    env.create_operation_account().await;
+    env.add_operators(
+        vec![whitelist_manager.pubkey()],
+        operation_role::WHITELIST_MANAGER,
+    )
+    .await;
+    let mint = env
+        .create_mint_2022(6, &[MintExtension::PermanentDelegate])
+        .await;
+    let support_mint_associated = client::support_mint_associated_address(&mint).0;
+
+    let result = env
+        .process(
+            &[client::create_support_mint_associated(
+                stranger.pubkey(),
+                mint,
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    env.process(
+        &[client::create_support_mint_associated(
+            whitelist_manager.pubkey(),
+            mint,
+        )],
+        &[&whitelist_manager],
+    )
+    .await
+    .unwrap();
+    let record: SupportMintAssociated = env.anchor_account(support_mint_associated).await;
+    assert_eq!(record.mint, mint);
+    assert!(record.is_extension_reviewed(
+        anchor_spl::token_2022::spl_token_2022::extension::ExtensionType::PermanentDelegate
+    ));
+
+    let result = env
+        .process(
+            &[client::close_support_mint_associated(
+                stranger.pubkey(),
+                mint,
+            )],
+            &[&stranger],
+        )
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    env.process(
+        &[client::close_support_mint_associated(admin.pubkey(), mint)],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    assert!(env.get_account(support_mint_associated).await.is_none());
+}
@@ -0,0 +1,168 @@
+//! 创建池子,包括带扩展的 Token-2022 mint
+use my_clmm::client;
+use my_clmm::error::ErrorCode;
+use my_clmm::libraries::tick_math;
+use my_clmm::states::*;
+use my_clmm_tests::*;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn create_pool() {
+    let mut env = TestEnv::start().await;
+    let creator = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+    let mint_a = env.create_mint(6).await;
+    let mint_b = env.create_mint(9).await;
+    let (token_mint_0, token_mint_1) = sort_mints(mint_a, mint_b);
+
+    // token_mint_0 必须小于 token_mint_1
+    let instruction = client::create_pool(
+        creator.pubkey(),
+        amm_config,
+        token_mint_1,
+        anchor_spl::token::ID,
+        token_mint_0,
+        anchor_spl::token::ID,
+        tick_math::get_sqrt_price_at_tick(0).unwrap(),
+        0,
+        client::CreatePoolOptions::default(),
+    );
+    assert!(env.process(&[instruction], &[&creator]).await.is_err());
+
+    let pool_state = env
+        .create_pool(
+            &creator,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .unwrap();
+    let pool: PoolState = env.zero_copy_account(pool_state).await;
+    assert_eq!({ pool.amm_config }, amm_config);
+    assert_eq!({ pool.owner }, creator.pubkey());
+    assert_eq!({ pool.token_mint_0 }, token_mint_0);
+    assert_eq!({ pool.token_mint_1 }, token_mint_1);
+    assert_eq!(
+        { pool.token_vault_0 },
+        client::pool_vault_address(&pool_state, &token_mint_0).0
+    );
+    assert_eq!(
+        { pool.token_vault_1 },
+        client::pool_vault_address(&pool_state, &token_mint_1).0
+    );
+    assert_eq!(
+        { pool.observation_key },
+        client::observation_address(&pool_state).0
+    );
+    assert_eq!({ pool.tick_spacing }, 60);
+    assert_eq!({ pool.tick_current }, 0);
+    assert_eq!({ pool.liquidity }, 0);
+    for reward_info in pool.reward_infos {
+        assert!(!reward_info.initialized());
+        assert_eq!({ reward_info.authority }, creator.pubkey());
+    }
+    assert_eq!(env.token_balance(pool.token_vault_0).await, 0);
+    assert_eq!(env.token_balance(pool.token_vault_1).await, 0);
+    let observation: ObservationState = env
+        .zero_copy_account(client::observation_address(&pool_state).0)
+        .await;
+    assert_eq!({ observation.pool_id }, pool_state);
+    let bitmap: TickArrayBitmapExtension = env
+        .zero_copy_account(client::tick_array_bitmap_extension_address(&pool_state).0)
+        .await;
+    assert_eq!({ bitmap.pool_id }, pool_state);
+
+    // 同一个配置下同一对代币只能有一个池子
+    let result = env
+        .create_pool(
+            &creator,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn create_pool_with_transfer_fee_mint() {
+    let mut env = TestEnv::start().await;
+    let creator = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+    let mint_a = env
+        .create_mint_2022(
+            6,
+            &[MintExtension::TransferFee {
+                basis_points: 100,
+                maximum_fee: u64::MAX,
+            }],
+        )
+        .await;
+    let mint_b = env.create_mint(6).await;
+
+    let pool_state = env
+        .create_pool(
+            &creator,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .unwrap();
+    let vault = client::pool_vault_address(&pool_state, &mint_a).0;
+    assert_eq!(
+        env.get_account(vault).await.unwrap().owner,
+        anchor_spl::token_2022::ID
+    );
+    assert_eq!(env.token_balance(vault).await, 0);
+}
+
+#[tokio::test]
+async fn create_pool_with_unsupported_mint() {
+    let mut env = TestEnv::start().await;
+    let admin = admin();
+    let creator = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+    env.create_operation_account().await;
+    let mint_a = env
+        .create_mint_2022(6, &[MintExtension::PermanentDelegate])
+        .await;
+    let mint_b = env.create_mint(6).await;
+
+    let result = env
+        .create_pool(
+            &creator,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .map(|_| ());
+    assert_error(result, ErrorCode::NotSupportMint);
+
+    // 加入链上白名单后,审核时已经存在的扩展也视为支持
+    env.process(
+        &[client::create_support_mint_associated(
+            admin.pubkey(),
+            mint_a,
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let (token_mint_0, _) = sort_mints(mint_a, mint_b);
+    let options = client::CreatePoolOptions {
+        with_support_mint_0: token_mint_0 == mint_a,
+        with_support_mint_1: token_mint_0 != mint_a,
+        ..Default::default()
+    };
+    env.create_pool(&creator, amm_config, mint_a, mint_b, options)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,350 @@
+//! 初始化奖励,依次填满池子的三个奖励槽位
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+};
+use my_clmm::client;
+use my_clmm::error::ErrorCode;
+use my_clmm::instructions::InitializeRewardParam;
+use my_clmm::libraries::fixed_point_64;
+use my_clmm::states::*;
+use my_clmm_tests::*;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+struct RewardFixture {
+    env: TestEnv,
+    creator: Keypair,
+    amm_config: Pubkey,
+    pool_state: Pubkey,
+    token_mint_0: Pubkey,
+}
+
+/// 由 `creator` 创建一个 spl-token 池子,运营账户已经创建
+async fn setup() -> RewardFixture {
+    let mut env = TestEnv::start().await;
+    let creator = env.new_user().await;
+    let amm_config = env.create_amm_config(0).await;
+    env.create_operation_account().await;
+    let mint_a = env.create_mint(6).await;
+    let mint_b = env.create_mint(6).await;
+    let pool_state = env
+        .create_pool(
+            &creator,
+            amm_config,
+            mint_a,
+            mint_b,
+            client::CreatePoolOptions::default(),
+        )
+        .await
+        .unwrap();
+    RewardFixture {
+        env,
+        creator,
+        amm_config,
+        pool_state,
+        token_mint_0: sort_mints(mint_a, mint_b).0,
+    }
+}
+
+/// 一分钟后开始,持续 `period` 秒,每秒发放一个代币
+async fn reward_param(env: &mut TestEnv, period: u64) -> InitializeRewardParam {
+    let open_time = env.now().await + 60;
+    InitializeRewardParam {
+        open_time,
+        end_time: open_time + period,
+        emissions_per_second_x64: fixed_point_64::Q64,
+    }
+}
+
+impl RewardFixture {
+    /// 由 `funder` 用 `reward_mint` 初始化奖励,funder 的代币账户在这里创建
+    async fn initialize_reward(
+        &mut self,
+        funder: &Keypair,
+        reward_mint: Pubkey,
+        param: InitializeRewardParam,
+        with_support_mint: bool,
+    ) -> (TransactionResult, Pubkey) {
+        let funder_token_account = self
+            .env
+            .create_token_account(reward_mint, funder.pubkey())
+            .await;
+        let reward_token_program = self.env.token_program_of(reward_mint).await;
+        let result = self
+            .env
+            .process(
+                &[client::initialize_reward(
+                    funder.pubkey(),
+                    funder_token_account,
+                    self.amm_config,
+                    self.pool_state,
+                    reward_mint,
+                    reward_token_program,
+                    param,
+                    with_support_mint,
+                )],
+                &[funder],
+            )
+            .await;
+        (result, funder_token_account)
+    }
+}
+
+#[tokio::test]
+async fn initialize_reward() {
+    let mut fixture = setup().await;
+    let admin = admin();
+    let creator = fixture.creator.insecure_clone();
+    let reward_manager = fixture.env.new_user().await;
+    fixture
+        .env
+        .add_operators(
+            vec![reward_manager.pubkey()],
+            operation_role::REWARD_MANAGER,
+        )
+        .await;
+    let param = reward_param(&mut fixture.env, reward_period_limit::MIN_REWARD_PERIOD).await;
+    let reward_amount = reward_period_limit::MIN_REWARD_PERIOD;
+
+    // 第一个槽位: 池子的创建者使用带转账手续费的 Token-2022 代币
+    let reward_mint_0 = fixture
+        .env
+        .create_mint_2022(
+            6,
+            &[MintExtension::TransferFee {
+                basis_points: 100,
+                maximum_fee: u64::MAX,
+            }],
+        )
+        .await;
+    let (result, funder_token_account) = fixture
+        .initialize_reward(&creator, reward_mint_0, param, false)
+        .await;
+    result.unwrap();
+    let reward_vault_0 = client::pool_reward_vault_address(&fixture.pool_state, &reward_mint_0).0;
+    // vault 收到完整的奖励总量,转账手续费由 funder 额外支付
+    let mint_account = fixture.env.get_account(reward_mint_0).await.unwrap();
+    let mint =
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.data).unwrap();
+    let epoch = fixture
+        .env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .epoch;
+    let transfer_fee = mint
+        .get_extension::<TransferFeeConfig>()
+        .unwrap()
+        .calculate_inverse_epoch_fee(epoch, reward_amount)
+        .unwrap();
+    assert!(transfer_fee > 0);
+    assert_eq!(
+        INITIAL_TOKEN_AMOUNT - fixture.env.token_balance(funder_token_account).await,
+        reward_amount + transfer_fee
+    );
+    assert_eq!(
+        fixture.env.token_balance(reward_vault_0).await,
+        reward_amount
+    );
+    let pool: PoolState = fixture.env.zero_copy_account(fixture.pool_state).await;
+    let reward_info = { pool.reward_infos }[0];
+    assert_eq!({ reward_info.token_mint }, reward_mint_0);
+    assert_eq!({ reward_info.token_vault }, reward_vault_0);
+    assert_eq!({ reward_info.authority }, creator.pubkey());
+    assert_eq!({ reward_info.open_time }, param.open_time);
+    assert_eq!({ reward_info.end_time }, param.end_time);
+    assert_eq!(
+        { reward_info.emissions_per_second_x64 },
+        param.emissions_per_second_x64
+    );
+
+    // 同一个 mint 不能重复用作奖励,在创建 vault 之前就会失败
+    let (result, _) = fixture
+        .initialize_reward(&creator, reward_mint_0, param, false)
+        .await;
+    assert_error(result, ErrorCode::RewardTokenAlreadyInUse);
+
+    // 第二个槽位: 两个池子代币都还没有被用作奖励时,只能使用池子代币或白名单中的代币
+    let outside_mint = fixture.env.create_mint(6).await;
+    let (result, _) = fixture
+        .initialize_reward(&creator, outside_mint, param, false)
+        .await;
+    assert_error(result, ErrorCode::ExceptPoolVaultMint);
+    let token_mint_0 = fixture.token_mint_0;
+    fixture
+        .initialize_reward(&creator, token_mint_0, param, false)
+        .await
+        .0
+        .unwrap();
+
+    // 第三个槽位: 只有 admin 和奖励管理员可以初始化,池子的创建者不行
+    let (result, _) = fixture
+        .initialize_reward(&creator, outside_mint, param, false)
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+    fixture
+        .initialize_reward(&reward_manager, outside_mint, param, false)
+        .await
+        .0
+        .unwrap();
+    let pool: PoolState = fixture.env.zero_copy_account(fixture.pool_state).await;
+    let reward_infos = { pool.reward_infos };
+    assert_eq!({ reward_infos[1].token_mint }, token_mint_0);
+    assert_eq!({ reward_infos[2].token_mint }, outside_mint);
+    assert_eq!({ reward_infos[2].authority }, reward_manager.pubkey());
+
+    // 三个槽位都已经使用
+    let extra_mint = fixture.env.create_mint(6).await;
+    let (result, _) = fixture
+        .initialize_reward(&admin, extra_mint, param, false)
+        .await;
+    assert_error(result, ErrorCode::FullRewardInfo);
+}
+
+#[tokio::test]
+async fn initialize_reward_whitelist_mint() {
+    let mut fixture = setup().await;
+    let admin = admin();
+    let creator = fixture.creator.insecure_clone();
+    let param = reward_param(&mut fixture.env, reward_period_limit::MIN_REWARD_PERIOD).await;
+    let reward_mint_0 = fixture.env.create_mint(6).await;
+    fixture
+        .initialize_reward(&creator, reward_mint_0, param, false)
+        .await
+        .0
+        .unwrap();
+
+    // 第二个槽位可以使用运营账户白名单中的代币
+    let whitelist_mint = fixture.env.create_mint(9).await;
+    fixture
+        .env
+        .process(
+            &[client::update_operation_account(
+                admin.pubkey(),
+                2,
+                vec![whitelist_mint],
+                0,
+            )],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+    fixture
+        .initialize_reward(&creator, whitelist_mint, param, false)
+        .await
+        .0
+        .unwrap();
+
+    // admin 可以初始化第三个槽位
+    let reward_mint_2 = fixture.env.create_mint(6).await;
+    fixture
+        .initialize_reward(&admin, reward_mint_2, param, false)
+        .await
+        .0
+        .unwrap();
+    let pool: PoolState = fixture.env.zero_copy_account(fixture.pool_state).await;
+    let reward_infos = { pool.reward_infos };
+    assert_eq!({ reward_infos[1].token_mint }, whitelist_mint);
+    assert_eq!({ reward_infos[2].authority }, admin.pubkey());
+}
+
+#[tokio::test]
+async fn initialize_reward_not_approved() {
+    let mut fixture = setup().await;
+    let stranger = fixture.env.new_user().await;
+    let param = reward_param(&mut fixture.env, reward_period_limit::MIN_REWARD_PERIOD).await;
+    let reward_mint = fixture.env.create_mint(6).await;
+
+    let (result, _) = fixture
+        .initialize_reward(&stranger, reward_mint, param, false)
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+
+    // 只有 POOL_PAUSER 角色的运营人员也不行
+    fixture
+        .env
+        .add_operators(vec![stranger.pubkey()], operation_role::POOL_PAUSER)
+        .await;
+    let (result, _) = fixture
+        .initialize_reward(&stranger, reward_mint, param, false)
+        .await;
+    assert_error(result, ErrorCode::NotApproved);
+}
+
+#[tokio::test]
+async fn initialize_reward_unsupported_mint() {
+    let mut fixture = setup().await;
+    let admin = admin();
+    let creator = fixture.creator.insecure_clone();
+    let param = reward_param(&mut fixture.env, reward_period_limit::MIN_REWARD_PERIOD).await;
+    let reward_mint = fixture
+        .env
+        .create_mint_2022(6, &[MintExtension::PermanentDelegate])
+        .await;
+
+    let (result, _) = fixture
+        .initialize_reward(&creator, reward_mint, param, false)
+        .await;
+    assert_error(result, ErrorCode::NotSupportMint);
+
+    fixture
+        .env
+        .process(
+            &[client::create_support_mint_associated(
+                admin.pubkey(),
+                reward_mint,
+            )],
+            &[&admin],
+        )
+        .await
+        .unwrap();
+    let (result, funder_token_account) = fixture
+        .initialize_reward(&creator, reward_mint, param, true)
+        .await;
+    result.unwrap();
+    let reward_vault = client::pool_reward_vault_address(&fixture.pool_state, &reward_mint).0;
+    let reward_amount = reward_period_limit::MIN_REWARD_PERIOD;
+    assert_eq!(fixture.env.token_balance(reward_vault).await, reward_amount);
+    assert_eq!(
+        fixture.env.token_balance(funder_token_account).await,
+        INITIAL_TOKEN_AMOUNT - reward_amount
+    );
+}
+
+#[tokio::test]
+async fn initialize_reward_invalid_period() {
+    let mut fixture = setup().await;
+    let creator = fixture.creator.insecure_clone();
+    let reward_mint = fixture.env.create_mint(6).await;
+
+    for period in [
+        reward_period_limit::MIN_REWARD_PERIOD - 1,
+        reward_period_limit::MAX_REWARD_PERIOD + 1,
+    ] {
+        let param = reward_param(&mut fixture.env, period).await;
+        let (result, _) = fixture
+            .initialize_reward(&creator, reward_mint, param, false)
+            .await;
+        assert_error(result, ErrorCode::InvalidRewardPeriod);
+    }
+
+    // 开始时间已经过去
+    let mut param = reward_param(&mut fixture.env, reward_period_limit::MIN_REWARD_PERIOD).await;
+    param.open_time -= 3600;
+    param.end_time -= 3600;
+    let (result, _) = fixture
+        .initialize_reward(&creator, reward_mint, param, false)
+        .await;
+    assert_error(result, ErrorCode::InvalidRewardInitParam);
+
+    let param = reward_param(&mut fixture.env, reward_period_limit::MAX_REWARD_PERIOD).await;
+    fixture
+        .initialize_reward(&creator, reward_mint, param, false)
+        .await
+        .0
+        .unwrap();
+}
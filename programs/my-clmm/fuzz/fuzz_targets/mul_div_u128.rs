@@ -0,0 +1,35 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use my_clmm::libraries::big_num::{U128, U512};
+use my_clmm::libraries::full_math::MulDiv;
+use my_clmm_fuzz::{reference_mul_div_checked, reference_to_underflow_u64, u512_from_words};
+
+fuzz_target!(|data: ([u64; 2], [u64; 2], [u64; 2])| {
+    let (val, num, denom) = (U128(data.0), U128(data.1), U128(data.2));
+    let max = u512_from_words(&U128::MAX.0);
+    let (val_512, num_512, denom_512) = (
+        u512_from_words(&val.0),
+        u512_from_words(&num.0),
+        u512_from_words(&denom.0),
+    );
+
+    let floor = reference_mul_div_checked(val_512, num_512, denom_512, false, max);
+    assert_eq!(
+        val.mul_div_floor(num, denom).map(|r| u512_from_words(&r.0)),
+        floor
+    );
+
+    let ceil = reference_mul_div_checked(val_512, num_512, denom_512, true, max);
+    assert_eq!(
+        val.mul_div_ceil(num, denom).map(|r| u512_from_words(&r.0)),
+        ceil
+    );
+
+    let expected = reference_to_underflow_u64(val_512);
+    assert_eq!(val.to_underflow_u64(), expected);
+    // 能放进 u64 的值在各个位宽下的结果必须一致
+    if val_512 <= U512::from(u64::MAX) {
+        assert_eq!(val.low_u64().to_underflow_u64(), expected);
+    }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use my_clmm::libraries::big_num::U512;
+use my_clmm::libraries::full_math::MulDiv;
+use my_clmm_fuzz::{reference_mul_div_checked, reference_to_underflow_u64};
+
+fuzz_target!(|data: (u64, u64, u64)| {
+    let (val, num, denom) = data;
+    let max = U512::from(u64::MAX);
+    let (val_512, num_512, denom_512) = (U512::from(val), U512::from(num), U512::from(denom));
+
+    let floor = reference_mul_div_checked(val_512, num_512, denom_512, false, max);
+    assert_eq!(val.mul_div_floor(num, denom).map(U512::from), floor);
+
+    let ceil = reference_mul_div_checked(val_512, num_512, denom_512, true, max);
+    assert_eq!(val.mul_div_ceil(num, denom).map(U512::from), ceil);
+
+    assert_eq!(val.to_underflow_u64(), reference_to_underflow_u64(val_512));
+});
@@ -0,0 +1,48 @@
+//! U512 reference for fuzzing `MulDiv` over every width.
+//!
+//! Every width fits in U512 even after `val * num + denom - 1`, so the reference
+//! result is always exact and can be compared with the width under test.
+//!
+//! Run from `programs/my-clmm` with `cargo fuzz run mul_div_u64` (or `mul_div_u128`, `mul_div_u256`).
+
+use my_clmm::libraries::big_num::U512;
+
+/// Exact `floor(val * num / denom)` or `ceil(val * num / denom)`, `None` only if `denom` is zero.
+pub fn reference_mul_div(val: U512, num: U512, denom: U512, round_up: bool) -> Option<U512> {
+    if denom.is_zero() {
+        return None;
+    }
+    let product = val * num;
+    let r = if round_up {
+        (product + denom - U512::one()) / denom
+    } else {
+        product / denom
+    };
+    Some(r)
+}
+
+/// Reference result narrowed to a width whose maximum value is `max`, `None` if it overflows.
+pub fn reference_mul_div_checked(
+    val: U512,
+    num: U512,
+    denom: U512,
+    round_up: bool,
+    max: U512,
+) -> Option<U512> {
+    reference_mul_div(val, num, denom, round_up).filter(|r| *r <= max)
+}
+
+/// What `to_underflow_u64` must return for `value` regardless of width.
+pub fn reference_to_underflow_u64(value: U512) -> u64 {
+    if value <= U512::from(u64::MAX) {
+        value.low_u64()
+    } else {
+        0
+    }
+}
+
+pub fn u512_from_words(words: &[u64]) -> U512 {
+    let mut ret = [0u64; 8];
+    ret[..words.len()].copy_from_slice(words);
+    U512(ret)
+}
@@ -0,0 +1,279 @@
+//! 每条指令的构造函数,返回的 `Instruction` 可以直接放进 `solana_sdk::transaction::Transaction`
+//!
+//! 需要签名的账户由调用者传入,PDA、系统程序和 sysvar 会自动填充
+use super::pda::*;
+use crate::instructions::{AmmConfigUpdate, InitializeRewardParam};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, sysvar};
+use anchor_lang::{system_program, InstructionData};
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// 1.创建 AMM 配置,`owner` 必须是 admin
+pub fn create_amm_config(
+    owner: Pubkey,
+    index: u16,
+    tick_spacing: u16,
+    trade_fee_rate: u32,
+    protocol_fee_rate: u32,
+    fund_fee_rate: u32,
+) -> Instruction {
+    instruction(
+        crate::accounts::CreateAmmConfig {
+            owner,
+            amm_config: amm_config_address(index).0,
+            system_program: system_program::ID,
+        },
+        crate::instruction::CreateAmmConfig {
+            index,
+            tick_spacing,
+            trade_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+        },
+    )
+}
+
+/// 2.更新 AMM 配置,`owner` 必须是 admin
+pub fn update_amm_config(
+    owner: Pubkey,
+    amm_config: Pubkey,
+    update: AmmConfigUpdate,
+) -> Instruction {
+    instruction(
+        crate::accounts::UpdateAmmConfig { owner, amm_config },
+        crate::instruction::UpdateAmmConfig { update },
+    )
+}
+
+/// create_pool 中的可选账户,只有对应的账户已经在链上创建时才需要传入
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CreatePoolOptions {
+    /// 配置需要权限时,传入 `pool_creator` 的 PoolCreatorPermission
+    pub with_creator_permission: bool,
+    /// 传入 token_mint_0 的 SupportMintAssociated
+    pub with_support_mint_0: bool,
+    /// 传入 token_mint_1 的 SupportMintAssociated
+    pub with_support_mint_1: bool,
+}
+
+/// 3.创建池子,`token_mint_0` 必须小于 `token_mint_1`,token program 必须是对应 mint 的 owner
+#[allow(clippy::too_many_arguments)]
+pub fn create_pool(
+    pool_creator: Pubkey,
+    amm_config: Pubkey,
+    token_mint_0: Pubkey,
+    token_program_0: Pubkey,
+    token_mint_1: Pubkey,
+    token_program_1: Pubkey,
+    sqrt_price_x64: u128,
+    open_time: u64,
+    options: CreatePoolOptions,
+) -> Instruction {
+    let pool_state = pool_address(&amm_config, &token_mint_0, &token_mint_1).0;
+    instruction(
+        crate::accounts::CreatePool {
+            pool_creator,
+            amm_config,
+            pool_state,
+            token_mint_0,
+            token_mint_1,
+            token_vault_0: pool_vault_address(&pool_state, &token_mint_0).0,
+            token_vault_1: pool_vault_address(&pool_state, &token_mint_1).0,
+            observation_state: observation_address(&pool_state).0,
+            tick_array_bitmap: tick_array_bitmap_extension_address(&pool_state).0,
+            token_program_0,
+            token_program_1,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            pool_creator_permission: options
+                .with_creator_permission
+                .then(|| pool_creator_permission_address(&amm_config, &pool_creator).0),
+            support_mint_associated_0: options
+                .with_support_mint_0
+                .then(|| support_mint_associated_address(&token_mint_0).0),
+            support_mint_associated_1: options
+                .with_support_mint_1
+                .then(|| support_mint_associated_address(&token_mint_1).0),
+        },
+        crate::instruction::CreatePool {
+            sqrt_price_x64,
+            open_time,
+        },
+    )
+}
+
+/// 4.更新池子状态,`authority` 必须是 admin 或拥有 POOL_PAUSER 角色的运营人员,
+/// 只有不是 admin 时才传入运营账户
+pub fn update_pool_status(authority: Pubkey, pool_state: Pubkey, status: u8) -> Instruction {
+    instruction(
+        crate::accounts::UpdatePoolStatus {
+            authority,
+            pool_state,
+            operation_state: (authority != crate::admin::id()).then(|| operation_address().0),
+        },
+        crate::instruction::UpdatePoolStatus { status },
+    )
+}
+
+/// 5.创建运营账户,`owner` 必须是 admin
+pub fn create_operation_account(owner: Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::CreateOperationAccount {
+            owner,
+            operation_state: operation_address().0,
+            system_program: system_program::ID,
+        },
+        crate::instruction::CreateOperationAccount {},
+    )
+}
+
+/// 6.更新运营账户,`param` 的含义见 `my_clmm::update_operation_account`,
+/// `owner` 同时支付扩容所需的租金
+pub fn update_operation_account(
+    owner: Pubkey,
+    param: u8,
+    keys: Vec<Pubkey>,
+    roles: u8,
+) -> Instruction {
+    instruction(
+        crate::accounts::UpdateOperationAccount {
+            owner,
+            operation_state: operation_address().0,
+            system_program: system_program::ID,
+        },
+        crate::instruction::UpdateOperationAccount { param, keys, roles },
+    )
+}
+
+/// 7.转移池子中奖励的 authority,`authority` 必须是 admin
+pub fn transfer_reward_owner(
+    authority: Pubkey,
+    pool_state: Pubkey,
+    new_owner: Pubkey,
+) -> Instruction {
+    instruction(
+        crate::accounts::TransferRewardOwner {
+            authority,
+            pool_state,
+        },
+        crate::instruction::TransferRewardOwner { new_owner },
+    )
+}
+
+/// 8.初始化奖励,`with_support_mint` 为 true 时传入奖励代币的 SupportMintAssociated
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_reward(
+    reward_funder: Pubkey,
+    funder_token_account: Pubkey,
+    amm_config: Pubkey,
+    pool_state: Pubkey,
+    reward_token_mint: Pubkey,
+    reward_token_program: Pubkey,
+    param: InitializeRewardParam,
+    with_support_mint: bool,
+) -> Instruction {
+    instruction(
+        crate::accounts::InitializeReward {
+            reward_funder,
+            funder_token_account,
+            amm_config,
+            pool_state,
+            operation_state: operation_address().0,
+            reward_token_mint,
+            reward_token_vault: pool_reward_vault_address(&pool_state, &reward_token_mint).0,
+            reward_token_program,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            support_mint_associated: with_support_mint
+                .then(|| support_mint_associated_address(&reward_token_mint).0),
+        },
+        crate::instruction::InitializeReward { param },
+    )
+}
+
+/// 9.更新 AMM 配置的状态,`owner` 必须是配置的 owner 或 admin
+pub fn update_amm_config_status(owner: Pubkey, amm_config: Pubkey, status: u8) -> Instruction {
+    instruction(
+        crate::accounts::UpdateAmmConfigStatus { owner, amm_config },
+        crate::instruction::UpdateAmmConfigStatus { status },
+    )
+}
+
+/// 10.授权 `creator` 在 `amm_config` 下创建池子,`owner` 必须是 admin
+pub fn create_pool_creator_permission(
+    owner: Pubkey,
+    amm_config: Pubkey,
+    creator: Pubkey,
+) -> Instruction {
+    instruction(
+        crate::accounts::CreatePoolCreatorPermission {
+            owner,
+            amm_config,
+            creator,
+            pool_creator_permission: pool_creator_permission_address(&amm_config, &creator).0,
+            system_program: system_program::ID,
+        },
+        crate::instruction::CreatePoolCreatorPermission {},
+    )
+}
+
+/// 11.撤销 `creator` 的授权,租金退还给 `owner`
+pub fn close_pool_creator_permission(
+    owner: Pubkey,
+    amm_config: Pubkey,
+    creator: Pubkey,
+) -> Instruction {
+    instruction(
+        crate::accounts::ClosePoolCreatorPermission {
+            owner,
+            pool_creator_permission: pool_creator_permission_address(&amm_config, &creator).0,
+        },
+        crate::instruction::ClosePoolCreatorPermission {},
+    )
+}
+
+/// 12.把 `token_mint` 加入链上白名单,`authority` 必须是 admin 或拥有 WHITELIST_MANAGER 角色的运营人员
+pub fn create_support_mint_associated(authority: Pubkey, token_mint: Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::CreateSupportMintAssociated {
+            authority,
+            operation_state: operation_address().0,
+            token_mint,
+            support_mint_associated: support_mint_associated_address(&token_mint).0,
+            system_program: system_program::ID,
+        },
+        crate::instruction::CreateSupportMintAssociated {},
+    )
+}
+
+/// 13.把 `token_mint` 移出链上白名单,租金退还给 `authority`
+pub fn close_support_mint_associated(authority: Pubkey, token_mint: Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::CloseSupportMintAssociated {
+            authority,
+            operation_state: operation_address().0,
+            support_mint_associated: support_mint_associated_address(&token_mint).0,
+        },
+        crate::instruction::CloseSupportMintAssociated {},
+    )
+}
+
+/// 14.把旧版本的运营账户迁移到新的运营账户,`owner` 必须是 admin
+pub fn migrate_operation_account(owner: Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::MigrateOperationAccount {
+            owner,
+            legacy_operation_state: legacy_operation_address().0,
+            operation_state: operation_address().0,
+            system_program: system_program::ID,
+        },
+        crate::instruction::MigrateOperationAccount {},
+    )
+}
@@ -0,0 +1,116 @@
+//! 账户数据解析
+//!
+//! 这里只校验 discriminator 和长度,调用者需要自己确认账户的 owner 是本程序
+use crate::states::*;
+use anchor_lang::__private::bytemuck;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, ZeroCopy};
+
+/// 解析 zero-copy 账户,直接借用 `data`,不会复制账户数据
+pub fn load_zero_copy<T: ZeroCopy>(data: &[u8]) -> Result<&T> {
+    check_discriminator::<T>(data)?;
+    let end = 8 + std::mem::size_of::<T>();
+    require_gte!(data.len(), end, AnchorErrorCode::AccountDidNotDeserialize);
+    bytemuck::try_from_bytes(&data[8..end])
+        .map_err(|_| AnchorErrorCode::AccountDidNotDeserialize.into())
+}
+
+/// 解析 Borsh 序列化的账户
+pub fn deserialize_account<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+fn check_discriminator<T: Discriminator>(data: &[u8]) -> Result<()> {
+    require_gte!(data.len(), 8, AnchorErrorCode::AccountDiscriminatorNotFound);
+    require!(
+        data[..8] == T::DISCRIMINATOR,
+        AnchorErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(())
+}
+
+/// 本程序的所有账户类型,zero-copy 账户借用原始数据
+#[derive(Debug)]
+pub enum ClmmAccount<'a> {
+    AmmConfig(AmmConfig),
+    PoolState(&'a PoolState),
+    ObservationState(&'a ObservationState),
+    TickArrayBitmapExtension(&'a TickArrayBitmapExtension),
+    TickArrayState(&'a TickArrayState),
+    OperationState(OperationState),
+    PoolCreatorPermission(PoolCreatorPermission),
+    SupportMintAssociated(SupportMintAssociated),
+}
+
+impl<'a> ClmmAccount<'a> {
+    /// 根据 discriminator 判断账户类型并解析
+    pub fn decode(data: &'a [u8]) -> Result<Self> {
+        require_gte!(data.len(), 8, AnchorErrorCode::AccountDiscriminatorNotFound);
+        let discriminator: [u8; 8] = data[..8].try_into().unwrap();
+        let account = match discriminator {
+            AmmConfig::DISCRIMINATOR => Self::AmmConfig(deserialize_account(data)?),
+            PoolState::DISCRIMINATOR => Self::PoolState(load_zero_copy(data)?),
+            ObservationState::DISCRIMINATOR => Self::ObservationState(load_zero_copy(data)?),
+            TickArrayBitmapExtension::DISCRIMINATOR => {
+                Self::TickArrayBitmapExtension(load_zero_copy(data)?)
+            }
+            TickArrayState::DISCRIMINATOR => Self::TickArrayState(load_zero_copy(data)?),
+            OperationState::DISCRIMINATOR => Self::OperationState(deserialize_account(data)?),
+            PoolCreatorPermission::DISCRIMINATOR => {
+                Self::PoolCreatorPermission(deserialize_account(data)?)
+            }
+            SupportMintAssociated::DISCRIMINATOR => {
+                Self::SupportMintAssociated(deserialize_account(data)?)
+            }
+            _ => return err!(AnchorErrorCode::AccountDiscriminatorMismatch),
+        };
+        Ok(account)
+    }
+
+    /// 账户类型的名字
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AmmConfig(_) => "AmmConfig",
+            Self::PoolState(_) => "PoolState",
+            Self::ObservationState(_) => "ObservationState",
+            Self::TickArrayBitmapExtension(_) => "TickArrayBitmapExtension",
+            Self::TickArrayState(_) => "TickArrayState",
+            Self::OperationState(_) => "OperationState",
+            Self::PoolCreatorPermission(_) => "PoolCreatorPermission",
+            Self::SupportMintAssociated(_) => "SupportMintAssociated",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_accounts() {
+        let mut data = vec![];
+        AmmConfig {
+            index: 3,
+            ..Default::default()
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        match ClmmAccount::decode(&data).unwrap() {
+            ClmmAccount::AmmConfig(amm_config) => assert_eq!(amm_config.index, 3),
+            account => panic!("unexpected account {}", account.name()),
+        }
+
+        let mut data = PoolState::DISCRIMINATOR.to_vec();
+        data.resize(PoolState::LEN, 0);
+        data[8 + 227..8 + 229].copy_from_slice(&60u16.to_le_bytes());
+        match ClmmAccount::decode(&data).unwrap() {
+            ClmmAccount::PoolState(pool_state) => assert_eq!({ pool_state.tick_spacing }, 60),
+            account => panic!("unexpected account {}", account.name()),
+        }
+        assert!(load_zero_copy::<PoolState>(&data[..PoolState::LEN - 1]).is_err());
+        assert!(load_zero_copy::<ObservationState>(&data).is_err());
+        assert!(ClmmAccount::decode(&[0; 8]).is_err());
+        assert!(ClmmAccount::decode(&[]).is_err());
+    }
+}
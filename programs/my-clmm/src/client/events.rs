@@ -0,0 +1,144 @@
+//! 从交易日志中解析 `emit!` 发出的事件
+//!
+//! `emit!` 把 discriminator 和 Borsh 序列化的事件用 `sol_log_data` 写成一行 `Program data: <base64>`。
+//! 日志中其他程序(包括被本程序 CPI 调用的程序)也可能输出 `Program data`,
+//! 这里根据 `invoke` 和 `success`/`failed` 维护调用栈,只解析本程序直接输出的数据
+use crate::states::*;
+use anchor_lang::__private::base64;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// 本程序的所有事件类型
+#[derive(Debug)]
+pub enum ClmmEvent {
+    PoolCreated(PoolCreatedEvent),
+    ConfigChange(ConfigChangeEvent),
+}
+
+impl ClmmEvent {
+    /// 根据 discriminator 判断事件类型并解析,`data` 是 base64 解码后的数据
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        require_gte!(data.len(), 8, AnchorErrorCode::InstructionDidNotDeserialize);
+        let discriminator: [u8; 8] = data[..8].try_into().unwrap();
+        let mut body = &data[8..];
+        let event = match discriminator {
+            PoolCreatedEvent::DISCRIMINATOR => {
+                Self::PoolCreated(AnchorDeserialize::deserialize(&mut body)?)
+            }
+            ConfigChangeEvent::DISCRIMINATOR => {
+                Self::ConfigChange(AnchorDeserialize::deserialize(&mut body)?)
+            }
+            _ => return err!(AnchorErrorCode::InstructionDidNotDeserialize),
+        };
+        Ok(event)
+    }
+
+    /// 事件类型的名字
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PoolCreated(_) => "PoolCreatedEvent",
+            Self::ConfigChange(_) => "ConfigChangeEvent",
+        }
+    }
+}
+
+/// 按顺序解析一笔交易日志中本程序发出的所有事件,无法识别的数据会被跳过
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<ClmmEvent> {
+    let program_id = crate::id().to_string();
+    let invoke = format!("Program {} invoke", program_id);
+    // 调用栈中每一层是否是本程序
+    let mut stack: Vec<bool> = vec![];
+    let mut events = vec![];
+    for log in logs.iter().map(AsRef::as_ref) {
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA) {
+            if stack.last() != Some(&true) {
+                continue;
+            }
+            // 每个事件只有一个切片,解析失败的数据不是本程序的事件
+            if let Some(event) = base64::decode(data)
+                .ok()
+                .and_then(|data| ClmmEvent::decode(&data).ok())
+            {
+                events.push(event);
+            }
+        } else if log.starts_with(&invoke) {
+            stack.push(true);
+        } else if log.starts_with("Program ") && log.contains(" invoke [") {
+            stack.push(false);
+        } else if log.starts_with("Program ")
+            && (log.ends_with(" success") || log.contains(" failed: "))
+        {
+            stack.pop();
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anchor_lang::Event;
+
+    #[test]
+    fn parse_events_of_this_program() {
+        let pool_created = PoolCreatedEvent {
+            token_mint_0: Pubkey::new_unique(),
+            token_mint_1: Pubkey::new_unique(),
+            tick_spacing: 60,
+            pool_state: Pubkey::new_unique(),
+            sqrt_price_x64: 1 << 64,
+            tick: -5,
+            token_vault_0: Pubkey::new_unique(),
+            token_vault_1: Pubkey::new_unique(),
+        };
+        let config_change = ConfigChangeEvent {
+            index: 2,
+            owner: Pubkey::new_unique(),
+            protocol_fee_rate: 120_000,
+            trade_fee_rate: 2500,
+            tick_spacing: 60,
+            fund_fee_rate: 40_000,
+            fund_owner: Pubkey::new_unique(),
+            status: 1,
+        };
+        let program_data = |data: Vec<u8>| format!("{}{}", PROGRAM_DATA, base64::encode(data));
+        let other_program = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", crate::id()),
+            "Program log: Instruction: CreatePool".to_string(),
+            format!("Program {} invoke [2]", other_program),
+            // CPI 调用的程序输出的数据不属于本程序
+            program_data(config_change.data()),
+            format!("Program {} success", other_program),
+            program_data(pool_created.data()),
+            program_data(vec![1, 2, 3]),
+            format!("Program {} success", crate::id()),
+            // 不在本程序的调用中
+            program_data(config_change.data()),
+            format!("Program {} invoke [1]", crate::id()),
+            program_data(config_change.data()),
+            format!("Program {} success", crate::id()),
+        ];
+        let events = parse_logs(&logs);
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            ClmmEvent::PoolCreated(event) => {
+                assert_eq!(event.pool_state, pool_created.pool_state);
+                assert_eq!(event.sqrt_price_x64, 1 << 64);
+                assert_eq!(event.tick, -5);
+            }
+            event => panic!("unexpected event {}", event.name()),
+        }
+        match &events[1] {
+            ClmmEvent::ConfigChange(event) => {
+                assert_eq!(event.index, 2);
+                assert_eq!(event.fund_owner, config_change.fund_owner);
+            }
+            event => panic!("unexpected event {}", event.name()),
+        }
+        assert!(ClmmEvent::decode(&[0; 4]).is_err());
+    }
+}
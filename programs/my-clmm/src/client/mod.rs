@@ -0,0 +1,27 @@
+//! 链下客户端使用的 SDK,需要开启 `client` feature
+//!
+//! - [`pda`]: 根据种子常量推导所有 PDA 地址
+//! - [`builder`]: 构造每条指令的 `Instruction`,自动填充 PDA 和程序账户
+//! - [`decode`]: 校验 discriminator 后解析账户数据,zero-copy 账户直接借用原始数据
+//! - [`quote`]: 链下模拟 swap 报价
+//! - [`events`]: 从交易日志中解析程序发出的事件
+//! - [`position`]: 仓位估值以及未结算的手续费和奖励
+//! - [`reward`]: 奖励的发放预测、APR 和剩余预算
+//! - [`router`]: 在多个池子中寻找 1~3 跳的最优路径,可以拆分到并行的池子
+pub mod builder;
+pub mod decode;
+pub mod events;
+pub mod pda;
+pub mod position;
+pub mod quote;
+pub mod reward;
+pub mod router;
+
+pub use builder::*;
+pub use decode::*;
+pub use events::*;
+pub use pda::*;
+pub use position::*;
+pub use quote::*;
+pub use reward::*;
+pub use router::*;
@@ -0,0 +1,86 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &crate::id())
+}
+
+/// `[AMM_CONFIG_SEED, index]`,index 使用大端序
+pub fn amm_config_address(index: u16) -> (Pubkey, u8) {
+    find(&[AMM_CONFIG_SEED.as_bytes(), &index.to_be_bytes()])
+}
+
+/// `[POOL_SEED, amm_config, token_mint_0, token_mint_1]`,token_mint_0 必须小于 token_mint_1
+pub fn pool_address(
+    amm_config: &Pubkey,
+    token_mint_0: &Pubkey,
+    token_mint_1: &Pubkey,
+) -> (Pubkey, u8) {
+    find(&[
+        POOL_SEED.as_bytes(),
+        amm_config.as_ref(),
+        token_mint_0.as_ref(),
+        token_mint_1.as_ref(),
+    ])
+}
+
+/// `[POOL_VAULT_SEED, pool_state, token_mint]`
+pub fn pool_vault_address(pool_state: &Pubkey, token_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[
+        POOL_VAULT_SEED.as_bytes(),
+        pool_state.as_ref(),
+        token_mint.as_ref(),
+    ])
+}
+
+/// `[OBSERVATION_SEED, pool_state]`
+pub fn observation_address(pool_state: &Pubkey) -> (Pubkey, u8) {
+    find(&[OBSERVATION_SEED.as_bytes(), pool_state.as_ref()])
+}
+
+/// `[POOL_TICK_ARRAY_BITMAP_SEED, pool_state]`
+pub fn tick_array_bitmap_extension_address(pool_state: &Pubkey) -> (Pubkey, u8) {
+    find(&[POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(), pool_state.as_ref()])
+}
+
+/// `[TICK_ARRAY_SEED, pool_state, start_index]`,start_index 使用大端序
+pub fn tick_array_address(pool_state: &Pubkey, start_index: i32) -> (Pubkey, u8) {
+    find(&[
+        TICK_ARRAY_SEED.as_bytes(),
+        pool_state.as_ref(),
+        &start_index.to_be_bytes(),
+    ])
+}
+
+/// `[OPERATION_SEED]`,整个程序只有一个
+pub fn operation_address() -> (Pubkey, u8) {
+    find(&[OPERATION_SEED.as_bytes()])
+}
+
+/// `[LEGACY_OPERATION_SEED]`,旧版本的运营账户
+pub fn legacy_operation_address() -> (Pubkey, u8) {
+    find(&[LEGACY_OPERATION_SEED.as_bytes()])
+}
+
+/// `[POOL_REWARD_VAULT_SEED, pool_state, reward_token_mint]`
+pub fn pool_reward_vault_address(pool_state: &Pubkey, reward_token_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[
+        POOL_REWARD_VAULT_SEED.as_bytes(),
+        pool_state.as_ref(),
+        reward_token_mint.as_ref(),
+    ])
+}
+
+/// `[POOL_CREATOR_PERMISSION_SEED, amm_config, creator]`
+pub fn pool_creator_permission_address(amm_config: &Pubkey, creator: &Pubkey) -> (Pubkey, u8) {
+    find(&[
+        POOL_CREATOR_PERMISSION_SEED.as_bytes(),
+        amm_config.as_ref(),
+        creator.as_ref(),
+    ])
+}
+
+/// `[SUPPORT_MINT_SEED, token_mint]`
+pub fn support_mint_associated_address(token_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[SUPPORT_MINT_SEED.as_bytes(), token_mint.as_ref()])
+}
@@ -0,0 +1,444 @@
+//! 仓位估值,以及还没有结算的手续费和奖励
+//!
+//! 程序中还没有 tick 和 position 账户,所以这里的 [`TickGrowth`] 和 [`PositionSnapshot`]
+//! 只包含估值需要的字段,由调用者从对应的账户中填入。
+//! 计算方式和 Uniswap V3 / Raydium CLMM 相同:
+//! - 仓位的代币数量按取出流动性的方向向下取整
+//! - `fee_growth_inside` 和 `reward_growth_inside` 使用 wrapping 减法,
+//!   新增的数量是 `(inside - inside_last) * liquidity / Q64` 向下取整
+use crate::error::ErrorCode;
+use crate::libraries::{fixed_point_64, price_math, sqrt_price_math, tick_math, MulDiv, U256};
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// tick 上记录的价格区间外的累积增长
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TickGrowth {
+    pub tick: i32,
+    pub fee_growth_outside_0_x64: u128,
+    pub fee_growth_outside_1_x64: u128,
+    pub reward_growths_outside_x64: [u128; REWARD_NUM],
+}
+
+/// 仓位上次结算时的状态
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionSnapshot {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub fee_growth_inside_0_last_x64: u128,
+    pub fee_growth_inside_1_last_x64: u128,
+    /// 已经结算但还没有领取的手续费
+    pub token_fees_owed_0: u64,
+    pub token_fees_owed_1: u64,
+    pub reward_growth_inside_last_x64: [u128; REWARD_NUM],
+    /// 已经结算但还没有领取的奖励
+    pub reward_amounts_owed: [u64; REWARD_NUM],
+}
+
+/// 仓位在某个价格下的代币数量和价值
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PositionValue {
+    pub amount_0: u64,
+    pub amount_1: u64,
+    /// 按 decimals 换算后,以 token_1 计价的价值
+    pub value_in_token_1: f64,
+}
+
+/// 对一个仓位估值,`tick_lower` 和 `tick_upper` 是仓位两端的 tick
+pub struct PositionValuation<'a> {
+    pub pool_state: &'a PoolState,
+    pub position: &'a PositionSnapshot,
+    pub tick_lower: &'a TickGrowth,
+    pub tick_upper: &'a TickGrowth,
+}
+
+impl<'a> PositionValuation<'a> {
+    pub fn new(
+        pool_state: &'a PoolState,
+        position: &'a PositionSnapshot,
+        tick_lower: &'a TickGrowth,
+        tick_upper: &'a TickGrowth,
+    ) -> Result<Self> {
+        require!(
+            position.tick_lower < position.tick_upper
+                && tick_lower.tick == position.tick_lower
+                && tick_upper.tick == position.tick_upper,
+            ErrorCode::InvaildTickIndex
+        );
+        Ok(Self {
+            pool_state,
+            position,
+            tick_lower,
+            tick_upper,
+        })
+    }
+
+    /// 当前价格下取出全部流动性能得到的代币数量
+    pub fn amounts(&self) -> Result<(u64, u64)> {
+        get_amounts_for_liquidity(
+            self.pool_state.tick_current,
+            self.pool_state.sqrt_price_x64,
+            self.position.tick_lower,
+            self.position.tick_upper,
+            self.position.liquidity,
+        )
+    }
+
+    /// 假设池子的价格变成 `price`(1 个 token_0 值多少个 token_1,已按 decimals 换算)时仓位的价值
+    pub fn value_at_price(&self, price: f64) -> Result<PositionValue> {
+        let decimals_0 = self.pool_state.mint_decimals_0;
+        let decimals_1 = self.pool_state.mint_decimals_1;
+        let sqrt_price_x64 = price_math::price_to_sqrt_price_x64(price, decimals_0, decimals_1)?;
+        let tick = if sqrt_price_x64 >= tick_math::MAX_SQRT_PRICE_X64 {
+            tick_math::MAX_TICK
+        } else {
+            tick_math::get_tick_at_sqrt_price(sqrt_price_x64)?
+        };
+        let (amount_0, amount_1) = get_amounts_for_liquidity(
+            tick,
+            sqrt_price_x64,
+            self.position.tick_lower,
+            self.position.tick_upper,
+            self.position.liquidity,
+        )?;
+        let ui_amount_0 = amount_0 as f64 / 10f64.powi(i32::from(decimals_0));
+        let ui_amount_1 = amount_1 as f64 / 10f64.powi(i32::from(decimals_1));
+        Ok(PositionValue {
+            amount_0,
+            amount_1,
+            value_in_token_1: ui_amount_0 * price + ui_amount_1,
+        })
+    }
+
+    /// 当前价格下仓位的价值
+    pub fn value(&self) -> Result<PositionValue> {
+        self.value_at_price(self.pool_state.price())
+    }
+
+    /// 仓位区间内每单位流动性累积的手续费
+    pub fn fee_growth_inside(&self) -> (u128, u128) {
+        let tick_current = self.pool_state.tick_current;
+        let inside = |global: u128, lower: u128, upper: u128| {
+            growth_inside(
+                tick_current,
+                self.tick_lower.tick,
+                self.tick_upper.tick,
+                global,
+                lower,
+                upper,
+            )
+        };
+        (
+            inside(
+                self.pool_state.fee_growth_global_0_x64,
+                self.tick_lower.fee_growth_outside_0_x64,
+                self.tick_upper.fee_growth_outside_0_x64,
+            ),
+            inside(
+                self.pool_state.fee_growth_global_1_x64,
+                self.tick_lower.fee_growth_outside_1_x64,
+                self.tick_upper.fee_growth_outside_1_x64,
+            ),
+        )
+    }
+
+    /// 可以领取的手续费,包含已经结算的 `token_fees_owed`
+    pub fn fees_owed(&self) -> Result<(u64, u64)> {
+        let (inside_0, inside_1) = self.fee_growth_inside();
+        let owed_0 = accrued(
+            inside_0,
+            self.position.fee_growth_inside_0_last_x64,
+            self.position.liquidity,
+        )?
+        .checked_add(self.position.token_fees_owed_0)
+        .ok_or(ErrorCode::CalculateOverflow)?;
+        let owed_1 = accrued(
+            inside_1,
+            self.position.fee_growth_inside_1_last_x64,
+            self.position.liquidity,
+        )?
+        .checked_add(self.position.token_fees_owed_1)
+        .ok_or(ErrorCode::CalculateOverflow)?;
+        Ok((owed_0, owed_1))
+    }
+
+    /// 仓位区间内每单位流动性累积的奖励,奖励的全局增长先更新到 `timestamp`,没有初始化的奖励为 0
+    pub fn reward_growths_inside(&self, timestamp: u64) -> Result<[u128; REWARD_NUM]> {
+        let reward_infos = { self.pool_state.reward_infos };
+        let globals = reward_growths_global_at(self.pool_state, timestamp)?;
+        let mut inside = [0; REWARD_NUM];
+        for (i, reward_info) in reward_infos.iter().enumerate() {
+            if !reward_info.initialized() {
+                continue;
+            }
+            inside[i] = growth_inside(
+                self.pool_state.tick_current,
+                self.tick_lower.tick,
+                self.tick_upper.tick,
+                globals[i],
+                self.tick_lower.reward_growths_outside_x64[i],
+                self.tick_upper.reward_growths_outside_x64[i],
+            );
+        }
+        Ok(inside)
+    }
+
+    /// 到 `timestamp` 为止可以领取的奖励,包含已经结算的 `reward_amounts_owed`
+    pub fn rewards_owed(&self, timestamp: u64) -> Result<[u64; REWARD_NUM]> {
+        let inside = self.reward_growths_inside(timestamp)?;
+        let mut owed = self.position.reward_amounts_owed;
+        for (i, owed) in owed.iter_mut().enumerate() {
+            *owed = accrued(
+                inside[i],
+                self.position.reward_growth_inside_last_x64[i],
+                self.position.liquidity,
+            )?
+            .checked_add(*owed)
+            .ok_or(ErrorCode::CalculateOverflow)?;
+        }
+        Ok(owed)
+    }
+}
+
+/// 取出 `liquidity` 能得到的代币数量,向下取整
+///
+/// 当前 tick 低于区间时仓位全部是 token_0,不低于上界时全部是 token_1
+pub fn get_amounts_for_liquidity(
+    tick_current: i32,
+    sqrt_price_current_x64: u128,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+) -> Result<(u64, u64)> {
+    let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower)?;
+    let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper)?;
+    if tick_current < tick_lower {
+        let amount_0 = sqrt_price_math::get_delta_amount_0_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            false,
+        )?;
+        Ok((amount_0, 0))
+    } else if tick_current < tick_upper {
+        let amount_0 = sqrt_price_math::get_delta_amount_0_unsigned(
+            sqrt_price_current_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            false,
+        )?;
+        let amount_1 = sqrt_price_math::get_delta_amount_1_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_current_x64,
+            liquidity,
+            false,
+        )?;
+        Ok((amount_0, amount_1))
+    } else {
+        let amount_1 = sqrt_price_math::get_delta_amount_1_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            false,
+        )?;
+        Ok((0, amount_1))
+    }
+}
+
+/// 把奖励的全局增长更新到 `timestamp`,和程序结算奖励时的计算相同:
+/// 奖励还没开始或者池子没有流动性时不增长,增长截止到 `end_time`
+pub fn reward_growths_global_at(
+    pool_state: &PoolState,
+    timestamp: u64,
+) -> Result<[u128; REWARD_NUM]> {
+    let liquidity = pool_state.liquidity;
+    let reward_infos = { pool_state.reward_infos };
+    let mut globals = [0; REWARD_NUM];
+    for (global, reward_info) in globals.iter_mut().zip(reward_infos.iter()) {
+        *global = reward_info.reward_growth_global_x64;
+        if !reward_info.initialized() || timestamp <= reward_info.open_time || liquidity == 0 {
+            continue;
+        }
+        let latest = timestamp.min(reward_info.end_time);
+        let last_update_time = reward_info.last_update_time;
+        if latest <= last_update_time {
+            continue;
+        }
+        let delta = U256::from(latest - last_update_time)
+            .mul_div_floor(
+                U256::from(reward_info.emissions_per_second_x64),
+                U256::from(liquidity),
+            )
+            .ok_or(ErrorCode::CalculateOverflow)?;
+        require_gte!(U256::from(u128::MAX), delta, ErrorCode::CalculateOverflow);
+        *global = global
+            .checked_add(delta.as_u128())
+            .ok_or(ErrorCode::CalculateOverflow)?;
+    }
+    Ok(globals)
+}
+
+/// `global - below - above`,其中 below/above 是区间下方/上方的增长
+fn growth_inside(
+    tick_current: i32,
+    tick_lower: i32,
+    tick_upper: i32,
+    global: u128,
+    lower_outside: u128,
+    upper_outside: u128,
+) -> u128 {
+    let below = if tick_current >= tick_lower {
+        lower_outside
+    } else {
+        global.wrapping_sub(lower_outside)
+    };
+    let above = if tick_current < tick_upper {
+        upper_outside
+    } else {
+        global.wrapping_sub(upper_outside)
+    };
+    global.wrapping_sub(below).wrapping_sub(above)
+}
+
+/// `(inside - inside_last) * liquidity / Q64`,向下取整
+fn accrued(inside: u128, inside_last: u128, liquidity: u128) -> Result<u64> {
+    let amount = U256::from(inside.wrapping_sub(inside_last))
+        .mul_div_floor(U256::from(liquidity), U256::from(fixed_point_64::Q64))
+        .ok_or(ErrorCode::CalculateOverflow)?;
+    require_gte!(U256::from(u64::MAX), amount, ErrorCode::CalculateOverflow);
+    Ok(amount.as_u64())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2 的幂,奖励的增长没有取整误差
+    const LIQUIDITY: u128 = 1 << 30;
+
+    fn pool_state(tick_current: i32) -> PoolState {
+        let mut pool_state = PoolState {
+            mint_decimals_0: 6,
+            mint_decimals_1: 6,
+            liquidity: LIQUIDITY,
+            sqrt_price_x64: tick_math::get_sqrt_price_at_tick(tick_current).unwrap(),
+            tick_current,
+            fee_growth_global_0_x64: 10 * fixed_point_64::Q64,
+            fee_growth_global_1_x64: 20 * fixed_point_64::Q64,
+            ..Default::default()
+        };
+        pool_state.reward_infos[0].token_mint = Pubkey::new_unique();
+        pool_state.reward_infos[0].open_time = 100;
+        pool_state.reward_infos[0].end_time = 200;
+        pool_state.reward_infos[0].last_update_time = 100;
+        pool_state.reward_infos[0].emissions_per_second_x64 = 1000 * fixed_point_64::Q64;
+        pool_state
+    }
+
+    fn position(tick_lower: i32, tick_upper: i32) -> PositionSnapshot {
+        PositionSnapshot {
+            tick_lower,
+            tick_upper,
+            liquidity: LIQUIDITY / 2,
+            token_fees_owed_0: 7,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn amounts_by_range() {
+        // 区间在当前价格上方,全部是 token_0
+        let (amount_0, amount_1) =
+            get_amounts_for_liquidity(0, 1 << 64, 10, 20, LIQUIDITY).unwrap();
+        assert!(amount_0 > 0);
+        assert_eq!(amount_1, 0);
+        // 区间在当前价格下方,全部是 token_1
+        let (amount_0, amount_1) =
+            get_amounts_for_liquidity(0, 1 << 64, -20, -10, LIQUIDITY).unwrap();
+        assert_eq!(amount_0, 0);
+        assert!(amount_1 > 0);
+        // 价格在区间中间时两种代币数量接近
+        let (amount_0, amount_1) =
+            get_amounts_for_liquidity(0, 1 << 64, -10, 10, LIQUIDITY).unwrap();
+        assert!(amount_0.abs_diff(amount_1) <= 1);
+    }
+
+    #[test]
+    fn value_at_hypothetical_prices() {
+        let pool_state = pool_state(0);
+        let position = position(-100, 100);
+        let tick_lower = TickGrowth {
+            tick: -100,
+            ..Default::default()
+        };
+        let tick_upper = TickGrowth {
+            tick: 100,
+            ..Default::default()
+        };
+        let valuation =
+            PositionValuation::new(&pool_state, &position, &tick_lower, &tick_upper).unwrap();
+        let current = valuation.value().unwrap();
+        assert_eq!(
+            (current.amount_0, current.amount_1),
+            valuation.amounts().unwrap()
+        );
+        // 价格远高于区间时全部换成 token_1,低于区间时全部换成 token_0
+        let high = valuation.value_at_price(2.0).unwrap();
+        assert_eq!(high.amount_0, 0);
+        assert!(high.amount_1 > current.amount_1);
+        let low = valuation.value_at_price(0.5).unwrap();
+        assert_eq!(low.amount_1, 0);
+        assert!(low.amount_0 > current.amount_0);
+        assert!((low.value_in_token_1 - low.amount_0 as f64 / 1e6 * 0.5).abs() < 1e-9);
+
+        assert!(PositionValuation::new(&pool_state, &position, &tick_upper, &tick_lower).is_err());
+    }
+
+    #[test]
+    fn fees_and_rewards_owed() {
+        let pool_state = pool_state(0);
+        let mut position = position(-100, 100);
+        position.fee_growth_inside_0_last_x64 = 2 * fixed_point_64::Q64;
+        // 区间下方累积了 1,上方累积了 3
+        let mut tick_lower = TickGrowth {
+            tick: -100,
+            fee_growth_outside_0_x64: fixed_point_64::Q64,
+            fee_growth_outside_1_x64: fixed_point_64::Q64,
+            ..Default::default()
+        };
+        let tick_upper = TickGrowth {
+            tick: 100,
+            fee_growth_outside_0_x64: 3 * fixed_point_64::Q64,
+            fee_growth_outside_1_x64: 3 * fixed_point_64::Q64,
+            ..Default::default()
+        };
+        let valuation =
+            PositionValuation::new(&pool_state, &position, &tick_lower, &tick_upper).unwrap();
+        assert_eq!(
+            valuation.fee_growth_inside(),
+            (6 * fixed_point_64::Q64, 16 * fixed_point_64::Q64)
+        );
+        let liquidity = (LIQUIDITY / 2) as u64;
+        assert_eq!(
+            valuation.fees_owed().unwrap(),
+            (4 * liquidity + 7, 16 * liquidity)
+        );
+
+        // 奖励开始前没有增长,结束后不再增长
+        assert_eq!(valuation.rewards_owed(100).unwrap(), [0; REWARD_NUM]);
+        let owed = valuation.rewards_owed(150).unwrap();
+        // 50 秒共发放 50_000,仓位占一半流动性
+        assert_eq!(owed[0], 25_000);
+        assert_eq!(owed[1..], [0, 0]);
+        assert_eq!(valuation.rewards_owed(1000).unwrap()[0], 50_000);
+
+        // inside_last 大于当前值时按 wrapping 计算
+        tick_lower.fee_growth_outside_0_x64 = 0;
+        let mut position = position;
+        position.fee_growth_inside_0_last_x64 = u128::MAX - fixed_point_64::Q64 + 1;
+        let valuation =
+            PositionValuation::new(&pool_state, &position, &tick_lower, &tick_upper).unwrap();
+        assert_eq!(valuation.fees_owed().unwrap().0, 8 * liquidity + 7);
+    }
+}
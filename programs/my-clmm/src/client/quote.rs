@@ -0,0 +1,591 @@
+//! 链下 swap 报价,使用和程序相同的 swap_math 模拟交易,不需要发送交易
+//!
+//! 和 Raydium 的 swap 一样,从当前价格开始沿交易方向逐个 tick 交易,
+//! 每一步交易到下一个初始化的 tick 或者价格限制,穿过 tick 时按 liquidity_net 更新流动性。
+//! 加载时需要传入交易路径上所有已经初始化的 tick array 账户,
+//! 路径上缺少 tick array 时返回 `NotEnoughTickArrayAccount`。
+//! Token-2022 的转账手续费不包含在报价中
+use super::decode::{deserialize_account, load_zero_copy};
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, swap_math, tick_math, tick_utils, MulDiv};
+use crate::states::*;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use std::collections::BTreeMap;
+
+/// 报价的结果
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// 用户需要支付的数量,包含手续费
+    pub amount_in: u64,
+    /// 用户得到的数量
+    pub amount_out: u64,
+    /// 交易手续费总额,等于 lp_fee + protocol_fee + fund_fee
+    pub fee_amount: u64,
+    pub lp_fee: u64,
+    pub protocol_fee: u64,
+    pub fund_fee: u64,
+    /// 交易结束后的价格
+    pub sqrt_price_x64: u128,
+    /// 交易结束后的 tick
+    pub tick: i32,
+    /// 交易结束后的流动性
+    pub liquidity: u128,
+    /// 交易经过的已经初始化的 tick array 的 start index,按交易方向排列,
+    /// 真实交易需要按这个顺序传入对应的账户
+    pub tick_array_start_indexes: Vec<i32>,
+}
+
+/// 从账户数据中加载的池子状态
+#[derive(Clone)]
+pub struct SwapQuoter {
+    pub pool_state: PoolState,
+    pub amm_config: AmmConfig,
+    /// 没有创建 TickArrayBitmapExtension 账户时所有位都是 0
+    pub tick_array_bitmap_extension: TickArrayBitmapExtension,
+    /// 已经加载的 tick array,key 是 start index
+    pub tick_arrays: BTreeMap<i32, TickArrayState>,
+}
+
+impl SwapQuoter {
+    /// 从 PoolState、AmmConfig、TickArrayBitmapExtension(没有创建时传 None)和 tick array 账户数据中加载
+    pub fn load(
+        pool_state_data: &[u8],
+        amm_config_data: &[u8],
+        bitmap_extension_data: Option<&[u8]>,
+        tick_array_data: &[&[u8]],
+    ) -> Result<Self> {
+        let pool_state = *load_zero_copy::<PoolState>(pool_state_data)?;
+        let amm_config = deserialize_account::<AmmConfig>(amm_config_data)?;
+        require_keys_eq!(
+            { pool_state.amm_config },
+            amm_config_key(&amm_config),
+            AnchorErrorCode::ConstraintAddress
+        );
+        let pool_id = pool_address(&pool_state);
+        let tick_array_bitmap_extension = match bitmap_extension_data {
+            Some(data) => {
+                let extension = *load_zero_copy::<TickArrayBitmapExtension>(data)?;
+                require_keys_eq!(
+                    { extension.pool_id },
+                    pool_id,
+                    AnchorErrorCode::ConstraintAddress
+                );
+                extension
+            }
+            None => TickArrayBitmapExtension::default(),
+        };
+        let mut tick_arrays = BTreeMap::new();
+        for data in tick_array_data {
+            let tick_array = *load_zero_copy::<TickArrayState>(data)?;
+            require_keys_eq!(
+                { tick_array.pool_id },
+                pool_id,
+                AnchorErrorCode::ConstraintAddress
+            );
+            tick_arrays.insert(tick_array.start_tick_index, tick_array);
+        }
+        Ok(Self {
+            pool_state,
+            amm_config,
+            tick_array_bitmap_extension,
+            tick_arrays,
+        })
+    }
+
+    /// 输入 `amount_in` 个代币(包含手续费)能得到的输出
+    pub fn quote_exact_in(
+        &self,
+        amount_in: u64,
+        zero_for_one: bool,
+        sqrt_price_limit_x64: Option<u128>,
+    ) -> Result<SwapQuote> {
+        self.quote(amount_in, zero_for_one, sqrt_price_limit_x64, true)
+    }
+
+    /// 得到 `amount_out` 个代币需要的输入,到达价格限制时得到的会少于 `amount_out`
+    pub fn quote_exact_out(
+        &self,
+        amount_out: u64,
+        zero_for_one: bool,
+        sqrt_price_limit_x64: Option<u128>,
+    ) -> Result<SwapQuote> {
+        self.quote(amount_out, zero_for_one, sqrt_price_limit_x64, false)
+    }
+
+    fn quote(
+        &self,
+        amount_specified: u64,
+        zero_for_one: bool,
+        sqrt_price_limit_x64: Option<u128>,
+        is_base_input: bool,
+    ) -> Result<SwapQuote> {
+        require!(amount_specified != 0, ErrorCode::ZeroAmountSpecified);
+        require!(
+            self.pool_state.get_status_by_bit(PoolStatusBitIndex::Swap),
+            ErrorCode::NotApproved
+        );
+        let tick_spacing = self.pool_state.tick_spacing;
+        let sqrt_price_limit_x64 = sqrt_price_limit_x64.unwrap_or(if zero_for_one {
+            tick_math::MIN_SQRT_PRICE_X64 + 1
+        } else {
+            tick_math::MAX_SQRT_PRICE_X64 - 1
+        });
+        if zero_for_one {
+            require!(
+                sqrt_price_limit_x64 < self.pool_state.sqrt_price_x64
+                    && sqrt_price_limit_x64 > tick_math::MIN_SQRT_PRICE_X64,
+                ErrorCode::SqrtPriceLimitOverflow
+            );
+        } else {
+            require!(
+                sqrt_price_limit_x64 > self.pool_state.sqrt_price_x64
+                    && sqrt_price_limit_x64 < tick_math::MAX_SQRT_PRICE_X64,
+                ErrorCode::SqrtPriceLimitOverflow
+            );
+        }
+
+        // 第一个 tick array: 当前价格所在的 tick array 没有初始化时,从交易方向上下一个初始化的开始
+        let current_start_index =
+            tick_utils::get_array_start_index(self.pool_state.tick_current, tick_spacing);
+        let mut is_match_current_tick_array =
+            self.is_tick_array_initialized(current_start_index)?;
+        let first_start_index = if is_match_current_tick_array {
+            current_start_index
+        } else {
+            self.next_initialized_tick_array(current_start_index, zero_for_one)?
+                .ok_or(ErrorCode::InsufficientLiquidityForDirection)?
+        };
+        let mut tick_array = self.tick_array(first_start_index)?;
+        let mut tick_array_start_indexes = vec![first_start_index];
+
+        let mut amount_remaining = amount_specified;
+        let mut quote = SwapQuote {
+            sqrt_price_x64: self.pool_state.sqrt_price_x64,
+            tick: self.pool_state.tick_current,
+            liquidity: self.pool_state.liquidity,
+            ..Default::default()
+        };
+        while amount_remaining != 0
+            && quote.sqrt_price_x64 != sqrt_price_limit_x64
+            && quote.tick < tick_math::MAX_TICK
+            && quote.tick > tick_math::MIN_TICK
+        {
+            let sqrt_price_start_x64 = quote.sqrt_price_x64;
+            let next_tick =
+                match tick_array.next_initialized_tick(quote.tick, tick_spacing, zero_for_one)? {
+                    Some(tick_state) => Some(tick_state),
+                    None if !is_match_current_tick_array => {
+                        is_match_current_tick_array = true;
+                        Some(tick_array.first_initialized_tick(zero_for_one)?)
+                    }
+                    None => None,
+                };
+            // 当前 tick array 中没有了,换到交易方向上下一个初始化的 tick array
+            let next_tick = match next_tick {
+                Some(tick_state) => *tick_state,
+                None => {
+                    let start_index = self
+                        .next_initialized_tick_array(tick_array.start_tick_index, zero_for_one)?
+                        .ok_or(ErrorCode::LiquidityInsufficient)?;
+                    tick_array = self.tick_array(start_index)?;
+                    tick_array_start_indexes.push(start_index);
+                    *tick_array.first_initialized_tick(zero_for_one)?
+                }
+            };
+
+            let tick_next = next_tick
+                .tick
+                .clamp(tick_math::MIN_TICK, tick_math::MAX_TICK);
+            let sqrt_price_next_x64 = tick_math::get_sqrt_price_at_tick(tick_next)?;
+            let target_price = if (zero_for_one && sqrt_price_next_x64 < sqrt_price_limit_x64)
+                || (!zero_for_one && sqrt_price_next_x64 > sqrt_price_limit_x64)
+            {
+                sqrt_price_limit_x64
+            } else {
+                sqrt_price_next_x64
+            };
+            let step = swap_math::compute_swap_step(
+                quote.sqrt_price_x64,
+                target_price,
+                quote.liquidity,
+                amount_remaining,
+                self.amm_config.trade_fee_rate,
+                is_base_input,
+                zero_for_one,
+            )?;
+            quote.sqrt_price_x64 = step.sqrt_price_next_x64;
+            let step_amount_in = step
+                .amount_in
+                .checked_add(step.fee_amount)
+                .ok_or(ErrorCode::MaxTokenOverflow)?;
+            amount_remaining = amount_remaining
+                .checked_sub(if is_base_input {
+                    step_amount_in
+                } else {
+                    step.amount_out
+                })
+                .ok_or(ErrorCode::CalculateOverflow)?;
+            quote.amount_in = quote
+                .amount_in
+                .checked_add(step_amount_in)
+                .ok_or(ErrorCode::MaxTokenOverflow)?;
+            quote.amount_out = quote
+                .amount_out
+                .checked_add(step.amount_out)
+                .ok_or(ErrorCode::MaxTokenOverflow)?;
+
+            // 协议费和基金费都按每一步的总手续费计算,剩下的归 LP
+            let protocol_fee =
+                self.fee_share(step.fee_amount, self.amm_config.protocol_fee_rate)?;
+            let fund_fee = self.fee_share(step.fee_amount, self.amm_config.fund_fee_rate)?;
+            quote.lp_fee += step
+                .fee_amount
+                .checked_sub(protocol_fee)
+                .and_then(|fee| fee.checked_sub(fund_fee))
+                .ok_or(ErrorCode::CalculateOverflow)?;
+            quote.protocol_fee += protocol_fee;
+            quote.fund_fee += fund_fee;
+            quote.fee_amount += step.fee_amount;
+
+            if quote.sqrt_price_x64 == sqrt_price_next_x64 {
+                // 穿过初始化的 tick,从右往左穿过时 liquidity_net 取相反数
+                if next_tick.is_initialized() {
+                    let liquidity_net = if zero_for_one {
+                        -next_tick.liquidity_net
+                    } else {
+                        next_tick.liquidity_net
+                    };
+                    quote.liquidity = liquidity_math::add_delta(quote.liquidity, liquidity_net)?;
+                }
+                quote.tick = if zero_for_one {
+                    tick_next - 1
+                } else {
+                    tick_next
+                };
+            } else if quote.sqrt_price_x64 != sqrt_price_start_x64 {
+                quote.tick = tick_math::get_tick_at_sqrt_price(quote.sqrt_price_x64)?;
+            }
+        }
+        quote.tick_array_start_indexes = tick_array_start_indexes;
+        Ok(quote)
+    }
+
+    fn fee_share(&self, fee_amount: u64, fee_rate: u32) -> Result<u64> {
+        fee_amount
+            .mul_div_floor(u64::from(fee_rate), u64::from(FEE_RATE_DENOMINATOR_VALUE))
+            .ok_or_else(|| error!(ErrorCode::CalculateOverflow))
+    }
+
+    fn is_tick_array_initialized(&self, start_index: i32) -> Result<bool> {
+        self.pool_state
+            .is_tick_array_initialized(start_index, Some(&self.tick_array_bitmap_extension))
+    }
+
+    /// 从 `start_index` 沿交易方向找到下一个初始化的 tick array,不包含 `start_index` 本身
+    fn next_initialized_tick_array(
+        &self,
+        start_index: i32,
+        zero_for_one: bool,
+    ) -> Result<Option<i32>> {
+        let tick_spacing = self.pool_state.tick_spacing;
+        let ticks_in_array = tick_utils::tick_count(tick_spacing);
+        let min_start_index = tick_utils::get_array_start_index(tick_math::MIN_TICK, tick_spacing);
+        let max_start_index = tick_utils::get_array_start_index(tick_math::MAX_TICK, tick_spacing);
+        let mut start_index = start_index;
+        loop {
+            start_index += if zero_for_one {
+                -ticks_in_array
+            } else {
+                ticks_in_array
+            };
+            if start_index < min_start_index || start_index > max_start_index {
+                return Ok(None);
+            }
+            if self.is_tick_array_initialized(start_index)? {
+                return Ok(Some(start_index));
+            }
+        }
+    }
+
+    fn tick_array(&self, start_index: i32) -> Result<&TickArrayState> {
+        self.tick_arrays
+            .get(&start_index)
+            .ok_or_else(|| error!(ErrorCode::NotEnoughTickArrayAccount))
+    }
+}
+
+fn amm_config_key(amm_config: &AmmConfig) -> Pubkey {
+    super::pda::amm_config_address(amm_config.index).0
+}
+
+fn pool_address(pool_state: &PoolState) -> Pubkey {
+    super::pda::pool_address(&{ pool_state.amm_config }, &{ pool_state.token_mint_0 }, &{
+        pool_state.token_mint_1
+    })
+    .0
+}
+
+#[cfg(test)]
+impl SwapQuoter {
+    /// 模拟在 [tick_lower, tick_upper) 添加 `liquidity` 的 position,更新 tick、tick array 和池子的 bitmap。
+    /// 只支持池子 tick_array_bitmap 范围内的 tick
+    pub(crate) fn add_position(&mut self, tick_lower: i32, tick_upper: i32, liquidity: u128) {
+        let tick_spacing = self.pool_state.tick_spacing;
+        let pool_id = pool_address(&self.pool_state);
+        for (tick, liquidity_net) in [
+            (tick_lower, liquidity as i128),
+            (tick_upper, -(liquidity as i128)),
+        ] {
+            let start_index = tick_utils::get_array_start_index(tick, tick_spacing);
+            let tick_array =
+                self.tick_arrays
+                    .entry(start_index)
+                    .or_insert_with(|| TickArrayState {
+                        pool_id,
+                        start_tick_index: start_index,
+                        ..Default::default()
+                    });
+            let tick_state =
+                &mut tick_array.ticks[((tick - start_index) / i32::from(tick_spacing)) as usize];
+            if !tick_state.is_initialized() {
+                tick_array.initialized_tick_count += 1;
+            }
+            tick_state.tick = tick;
+            tick_state.liquidity_net += liquidity_net;
+            tick_state.liquidity_gross += liquidity;
+
+            let offset = (start_index / tick_utils::tick_count(tick_spacing)
+                + tick_utils::TICK_ARRAY_BITMAP_SIZE) as usize;
+            self.pool_state.tick_array_bitmap[offset / 64] |= 1 << (offset % 64);
+        }
+        if (tick_lower..tick_upper).contains(&{ self.pool_state.tick_current }) {
+            self.pool_state.liquidity += liquidity;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::libraries::{fixed_point_64, sqrt_price_math};
+    use anchor_lang::__private::bytemuck;
+    use anchor_lang::ZeroCopy;
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+
+    fn account_data<T: ZeroCopy>(account: &T) -> Vec<u8> {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(account));
+        data
+    }
+
+    /// 价格为 1 的池子,按 `positions` 添加流动性后再从账户数据中加载
+    fn quoter(positions: &[(i32, i32, u128)]) -> SwapQuoter {
+        let amm_config = AmmConfig {
+            index: 1,
+            trade_fee_rate: 2500,
+            protocol_fee_rate: 120_000,
+            fund_fee_rate: 40_000,
+            tick_spacing: 10,
+            ..Default::default()
+        };
+        let mut quoter = SwapQuoter {
+            pool_state: PoolState {
+                amm_config: crate::client::amm_config_address(1).0,
+                tick_spacing: 10,
+                sqrt_price_x64: fixed_point_64::Q64,
+                tick_current: 0,
+                ..Default::default()
+            },
+            amm_config,
+            tick_array_bitmap_extension: TickArrayBitmapExtension::default(),
+            tick_arrays: BTreeMap::new(),
+        };
+        for &(tick_lower, tick_upper, liquidity) in positions {
+            quoter.add_position(tick_lower, tick_upper, liquidity);
+        }
+
+        let mut amm_config_data = vec![];
+        quoter
+            .amm_config
+            .try_serialize(&mut amm_config_data)
+            .unwrap();
+        let tick_array_data: Vec<_> = quoter.tick_arrays.values().map(account_data).collect();
+        let tick_array_data: Vec<_> = tick_array_data.iter().map(Vec::as_slice).collect();
+        SwapQuoter::load(
+            &account_data(&quoter.pool_state),
+            &amm_config_data,
+            None,
+            &tick_array_data,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn exact_in_and_exact_out_agree() {
+        let quoter = quoter(&[(-6000, 6000, LIQUIDITY)]);
+        for zero_for_one in [true, false] {
+            let exact_in = quoter
+                .quote_exact_in(1_000_000, zero_for_one, None)
+                .unwrap();
+            assert_eq!(exact_in.amount_in, 1_000_000);
+            assert_eq!(exact_in.fee_amount, 2500);
+            assert_eq!(exact_in.protocol_fee, 300);
+            assert_eq!(exact_in.fund_fee, 100);
+            assert_eq!(exact_in.lp_fee, 2100);
+            // 价格接近 1 时输出略少于扣除手续费后的输入
+            assert!(exact_in.amount_out < 997_500 && exact_in.amount_out > 996_000);
+            assert_eq!(exact_in.sqrt_price_x64 < fixed_point_64::Q64, zero_for_one);
+            assert_eq!(
+                exact_in.tick,
+                tick_math::get_tick_at_sqrt_price(exact_in.sqrt_price_x64).unwrap()
+            );
+            assert_eq!(exact_in.liquidity, LIQUIDITY);
+            // 当前价格所在的 tick array 没有初始化,从交易方向上第一个初始化的开始
+            let first = if zero_for_one { -6000 } else { 6000 };
+            assert_eq!(exact_in.tick_array_start_indexes, vec![first]);
+
+            // 得到相同的输出需要的输入不会超过 exact_in 的输入
+            let exact_out = quoter
+                .quote_exact_out(exact_in.amount_out, zero_for_one, None)
+                .unwrap();
+            assert_eq!(exact_out.amount_out, exact_in.amount_out);
+            assert!(exact_out.amount_in <= exact_in.amount_in);
+            assert!(exact_out.amount_in + 2 >= exact_in.amount_in);
+        }
+    }
+
+    #[test]
+    fn price_limit() {
+        let quoter = quoter(&[(-6000, 6000, LIQUIDITY)]);
+        let limit = tick_math::get_sqrt_price_at_tick(-10).unwrap();
+        let quote = quoter
+            .quote_exact_in(u64::MAX / 2, true, Some(limit))
+            .unwrap();
+        assert_eq!(quote.sqrt_price_x64, limit);
+        assert_eq!(quote.tick, -10);
+        assert!(quote.amount_in < u64::MAX / 2);
+
+        assert!(quoter
+            .quote_exact_in(1, true, Some(fixed_point_64::Q64))
+            .is_err());
+        assert!(quoter.quote_exact_in(0, true, None).is_err());
+    }
+
+    #[test]
+    fn cross_initialized_ticks() {
+        let quoter = quoter(&[(-6000, 6000, LIQUIDITY), (-1200, 1200, LIQUIDITY)]);
+        assert_eq!({ quoter.pool_state.liquidity }, 2 * LIQUIDITY);
+        let sqrt_price_cross = tick_math::get_sqrt_price_at_tick(-1200).unwrap();
+        let limit = tick_math::get_sqrt_price_at_tick(-3000).unwrap();
+        let quote = quoter
+            .quote_exact_in(u64::MAX / 2, true, Some(limit))
+            .unwrap();
+        assert_eq!(quote.sqrt_price_x64, limit);
+        assert_eq!(quote.tick, -3000);
+        // 穿过 -1200 后只剩下一个 position 的流动性
+        assert_eq!(quote.liquidity, LIQUIDITY);
+        assert_eq!(quote.tick_array_start_indexes, vec![-1200, -6000]);
+        let amount_out = sqrt_price_math::get_delta_amount_1_unsigned(
+            fixed_point_64::Q64,
+            sqrt_price_cross,
+            2 * LIQUIDITY,
+            false,
+        )
+        .unwrap()
+            + sqrt_price_math::get_delta_amount_1_unsigned(
+                sqrt_price_cross,
+                limit,
+                LIQUIDITY,
+                false,
+            )
+            .unwrap();
+        assert_eq!(quote.amount_out, amount_out);
+
+        // 停在初始化的 tick 上时,zero_for_one 的 tick 在它的左边
+        let quote = quoter
+            .quote_exact_out(quote.amount_out, true, Some(sqrt_price_cross))
+            .unwrap();
+        assert_eq!(quote.sqrt_price_x64, sqrt_price_cross);
+        assert_eq!(quote.tick, -1201);
+        assert_eq!(quote.liquidity, LIQUIDITY);
+        assert_eq!(quote.tick_array_start_indexes, vec![-1200]);
+
+        // 反方向从 tick array 1200 开始,穿过 1200 和 6000 后没有流动性
+        let limit = tick_math::get_sqrt_price_at_tick(3000).unwrap();
+        let quote = quoter
+            .quote_exact_in(u64::MAX / 2, false, Some(limit))
+            .unwrap();
+        assert_eq!(quote.tick, 3000);
+        assert_eq!(quote.liquidity, LIQUIDITY);
+        assert_eq!(quote.tick_array_start_indexes, vec![1200, 6000]);
+        assert_eq!(
+            quoter
+                .quote_exact_in(u64::MAX / 2, false, None)
+                .unwrap_err(),
+            ErrorCode::LiquidityInsufficient.into()
+        );
+    }
+
+    #[test]
+    fn missing_tick_array() {
+        let mut quoter = quoter(&[(-6000, 6000, LIQUIDITY), (-1200, 1200, LIQUIDITY)]);
+        quoter.tick_arrays.remove(&-6000);
+        let limit = tick_math::get_sqrt_price_at_tick(-1000).unwrap();
+        assert!(quoter.quote_exact_in(1_000, true, Some(limit)).is_ok());
+        let limit = tick_math::get_sqrt_price_at_tick(-3000).unwrap();
+        assert_eq!(
+            quoter
+                .quote_exact_in(u64::MAX / 2, true, Some(limit))
+                .unwrap_err(),
+            ErrorCode::NotEnoughTickArrayAccount.into()
+        );
+    }
+
+    #[test]
+    fn empty_pool() {
+        let quoter = quoter(&[]);
+        assert_eq!(
+            quoter.quote_exact_in(1_000, false, None).unwrap_err(),
+            ErrorCode::InsufficientLiquidityForDirection.into()
+        );
+    }
+
+    #[test]
+    fn swap_disabled() {
+        let mut quoter = quoter(&[(-6000, 6000, LIQUIDITY)]);
+        quoter
+            .pool_state
+            .set_status(1 << PoolStatusBitIndex::Swap as u8);
+        assert_eq!(
+            quoter.quote_exact_in(1_000, true, None).unwrap_err(),
+            ErrorCode::NotApproved.into()
+        );
+    }
+
+    #[test]
+    fn load_checks_pool() {
+        let quoter = quoter(&[(-6000, 6000, LIQUIDITY)]);
+        assert_eq!(
+            quoter.tick_arrays.keys().copied().collect::<Vec<_>>(),
+            vec![-6000, 6000]
+        );
+        let mut amm_config_data = vec![];
+        quoter
+            .amm_config
+            .try_serialize(&mut amm_config_data)
+            .unwrap();
+        let other_pool = TickArrayState {
+            pool_id: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        assert!(SwapQuoter::load(
+            &account_data(&quoter.pool_state),
+            &amm_config_data,
+            None,
+            &[&account_data(&other_pool)],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! 奖励的发放预测、APR 以及剩余预算
+//!
+//! 程序按秒发放奖励:池子有流动性时,每秒把 `emissions_per_second_x64 / Q64` 个奖励代币
+//! 按流动性分给当前价格所在区间内的仓位,没有流动性的时间不发放,
+//! 所以区间内流动性越少,每单位流动性得到的奖励越多
+use crate::error::ErrorCode;
+use crate::libraries::{fixed_point_64, tick_math, MulDiv, U256};
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+pub const SECONDS_PER_YEAR: u64 = 365 * SECONDS_PER_DAY;
+
+/// `seconds` 秒内发放的奖励数量,向下取整
+pub fn emissions_for(emissions_per_second_x64: u128, seconds: u64) -> Result<u64> {
+    let amount = U256::from(seconds)
+        .mul_div_floor(
+            U256::from(emissions_per_second_x64),
+            U256::from(fixed_point_64::Q64),
+        )
+        .ok_or(ErrorCode::CalculateOverflow)?;
+    require_gte!(U256::from(u64::MAX), amount, ErrorCode::CalculateOverflow);
+    Ok(amount.as_u64())
+}
+
+/// 每天发放的奖励数量
+pub fn emissions_per_day(reward_info: &RewardInfo) -> Result<u64> {
+    emissions_for(reward_info.emissions_per_second_x64, SECONDS_PER_DAY)
+}
+
+/// 区间内流动性为 `liquidity` 时,每单位流动性每天得到的奖励数量
+pub fn emissions_per_day_per_liquidity(reward_info: &RewardInfo, liquidity: u128) -> f64 {
+    if liquidity == 0 {
+        return 0.0;
+    }
+    reward_info.emissions_per_second_x64 as f64 / fixed_point_64::Q64 as f64
+        * SECONDS_PER_DAY as f64
+        / liquidity as f64
+}
+
+/// 在 `open_time` 到 `end_time` 之间发放 `total_amount` 个奖励需要的 `emissions_per_second_x64`,
+/// 向下取整,保证发放的数量不超过 `total_amount`,用于计算 `initialize_reward` 的参数
+pub fn emissions_per_second_x64_for(
+    total_amount: u64,
+    open_time: u64,
+    end_time: u64,
+) -> Result<u128> {
+    require_gt!(end_time, open_time, ErrorCode::InvalidRewardPeriod);
+    let emissions = U256::from(total_amount)
+        .mul_div_floor(
+            U256::from(fixed_point_64::Q64),
+            U256::from(end_time - open_time),
+        )
+        .ok_or(ErrorCode::CalculateOverflow)?;
+    require_gte!(
+        U256::from(u128::MAX),
+        emissions,
+        ErrorCode::CalculateOverflow
+    );
+    Ok(emissions.as_u128())
+}
+
+/// 一个奖励在某个时刻的预算
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RewardBudget {
+    /// 整个奖励周期计划发放的数量
+    pub scheduled: u64,
+    /// 已经发放的数量,包含上次结算之后到现在的部分
+    pub emitted: u64,
+    /// 已经发放但 LP 还没有领取的数量
+    pub unclaimed: u64,
+    /// 从现在到结束还会发放的数量,假设池子一直有流动性
+    pub remaining: u64,
+    pub vault_balance: u64,
+}
+
+impl RewardBudget {
+    /// 金库至少需要的余额
+    pub fn required(&self) -> u64 {
+        self.unclaimed.saturating_add(self.remaining)
+    }
+
+    /// 金库余额减去需要的余额,为负数时金库不足以支付剩余的奖励
+    pub fn surplus(&self) -> i128 {
+        i128::from(self.vault_balance) - i128::from(self.required())
+    }
+}
+
+/// 计算第 `reward_index` 个奖励在 `timestamp` 时的预算,`vault_balance` 是奖励金库的余额
+pub fn reward_budget(
+    pool_state: &PoolState,
+    reward_index: usize,
+    timestamp: u64,
+    vault_balance: u64,
+) -> Result<RewardBudget> {
+    let reward_info = initialized_reward(pool_state, reward_index)?;
+    let open_time = reward_info.open_time;
+    let end_time = reward_info.end_time;
+    let emissions_per_second_x64 = reward_info.emissions_per_second_x64;
+
+    // 和程序结算奖励时相同,池子有流动性时才会发放
+    let mut emitted = reward_info.reward_total_emissioned;
+    let last_update_time = reward_info.last_update_time.max(open_time);
+    let latest = timestamp.min(end_time);
+    if pool_state.liquidity != 0 && latest > last_update_time {
+        emitted = emitted
+            .checked_add(emissions_for(
+                emissions_per_second_x64,
+                latest - last_update_time,
+            )?)
+            .ok_or(ErrorCode::CalculateOverflow)?;
+    }
+    let start = timestamp.max(open_time);
+    let remaining = if start < end_time {
+        emissions_for(emissions_per_second_x64, end_time - start)?
+    } else {
+        0
+    };
+    Ok(RewardBudget {
+        scheduled: emissions_for(emissions_per_second_x64, end_time.saturating_sub(open_time))?,
+        emitted,
+        unclaimed: emitted.saturating_sub(reward_info.reward_claimed),
+        remaining,
+        vault_balance,
+    })
+}
+
+/// 计算 APR 需要的仓位区间和价格
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AprInput {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    /// 池子的价格,1 个 token_0 值多少个 token_1(已按 decimals 换算)
+    pub pool_price: f64,
+    /// 1 个奖励代币值多少个 token_1
+    pub reward_price: f64,
+    pub reward_decimals: u8,
+}
+
+/// 第 `reward_index` 个奖励给区间 `[tick_lower, tick_upper)` 内每单位流动性带来的年化收益率
+///
+/// 假设池子的价格和区间内的流动性保持不变,新增的仓位不会摊薄奖励。
+/// `timestamp` 时奖励不在发放期间,或者价格不在区间内时返回 0
+pub fn reward_apr(
+    pool_state: &PoolState,
+    reward_index: usize,
+    input: &AprInput,
+    timestamp: u64,
+) -> Result<f64> {
+    let reward_info = initialized_reward(pool_state, reward_index)?;
+    require!(
+        input.tick_lower < input.tick_upper,
+        ErrorCode::InvaildTickIndex
+    );
+    require!(
+        input.pool_price.is_finite()
+            && input.pool_price > 0.0
+            && input.reward_price.is_finite()
+            && input.reward_price >= 0.0,
+        ErrorCode::InvalidPrice
+    );
+    let active = timestamp >= reward_info.open_time && timestamp < reward_info.end_time;
+    let liquidity = pool_state.liquidity;
+    if !active || liquidity == 0 {
+        return Ok(0.0);
+    }
+    let decimals_0 = pool_state.mint_decimals_0;
+    let decimals_1 = pool_state.mint_decimals_1;
+    // 最小单位之间的价格的平方根
+    let sqrt_price =
+        (input.pool_price * 10f64.powi(i32::from(decimals_1) - i32::from(decimals_0))).sqrt();
+    let sqrt_price_lower =
+        tick_math::get_sqrt_price_at_tick(input.tick_lower)? as f64 / fixed_point_64::Q64 as f64;
+    let sqrt_price_upper =
+        tick_math::get_sqrt_price_at_tick(input.tick_upper)? as f64 / fixed_point_64::Q64 as f64;
+    if sqrt_price < sqrt_price_lower || sqrt_price >= sqrt_price_upper {
+        return Ok(0.0);
+    }
+    // 每单位流动性对应的代币数量,按 decimals 换算后以 token_1 计价
+    let amount_0 = (1.0 / sqrt_price - 1.0 / sqrt_price_upper) / 10f64.powi(i32::from(decimals_0));
+    let amount_1 = (sqrt_price - sqrt_price_lower) / 10f64.powi(i32::from(decimals_1));
+    let value = amount_0 * input.pool_price + amount_1;
+    let rewards_per_year = reward_info.emissions_per_second_x64 as f64 / fixed_point_64::Q64 as f64
+        * SECONDS_PER_YEAR as f64
+        / liquidity as f64
+        / 10f64.powi(i32::from(input.reward_decimals));
+    Ok(rewards_per_year * input.reward_price / value)
+}
+
+fn initialized_reward(pool_state: &PoolState, reward_index: usize) -> Result<RewardInfo> {
+    require_gt!(REWARD_NUM, reward_index, ErrorCode::InvalidRewardIndex);
+    let reward_info = { pool_state.reward_infos }[reward_index];
+    require!(reward_info.initialized(), ErrorCode::InvalidRewardIndex);
+    Ok(reward_info)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OPEN_TIME: u64 = 1_000_000;
+    const PERIOD: u64 = 10 * SECONDS_PER_DAY;
+
+    fn pool_state(liquidity: u128) -> PoolState {
+        let mut pool_state = PoolState {
+            mint_decimals_0: 6,
+            mint_decimals_1: 6,
+            liquidity,
+            sqrt_price_x64: fixed_point_64::Q64,
+            ..Default::default()
+        };
+        let reward_info = &mut pool_state.reward_infos[0];
+        reward_info.token_mint = Pubkey::new_unique();
+        reward_info.open_time = OPEN_TIME;
+        reward_info.end_time = OPEN_TIME + PERIOD;
+        reward_info.last_update_time = OPEN_TIME;
+        // 每秒 10 个
+        reward_info.emissions_per_second_x64 = 10 * fixed_point_64::Q64;
+        pool_state
+    }
+
+    #[test]
+    fn emissions() {
+        let pool_state = pool_state(1 << 20);
+        let reward_info = { pool_state.reward_infos }[0];
+        assert_eq!(emissions_per_day(&reward_info).unwrap(), 864_000);
+        assert_eq!(
+            emissions_per_day_per_liquidity(&reward_info, 1 << 20),
+            864_000.0 / (1 << 20) as f64
+        );
+        assert_eq!(emissions_per_day_per_liquidity(&reward_info, 0), 0.0);
+
+        let emissions =
+            emissions_per_second_x64_for(8_640_000, OPEN_TIME, OPEN_TIME + PERIOD).unwrap();
+        assert_eq!(emissions, 10 * fixed_point_64::Q64);
+        // 向下取整,不会超过总量
+        let emissions = emissions_per_second_x64_for(1_000, 0, 3).unwrap();
+        assert!(emissions_for(emissions, 3).unwrap() <= 1_000);
+        assert!(emissions_per_second_x64_for(1_000, 3, 3).is_err());
+    }
+
+    #[test]
+    #[test]
+    fn budget() {
+        let mut pool_state = pool_state(1 << 20);
+        pool_state.reward_infos[0].reward_total_emissioned = 500;
+        pool_state.reward_infos[0].reward_claimed = 200;
+        pool_state.reward_infos[0].last_update_time = OPEN_TIME + 50;
+
+        let budget = reward_budget(&pool_state, 0, OPEN_TIME + 100, 9_000_000).unwrap();
+        assert_eq!(budget.scheduled, 8_640_000);
+        assert_eq!(budget.emitted, 1_000);
+        assert_eq!(budget.unclaimed, 800);
+        assert_eq!(budget.remaining, 8_640_000 - 1_000);
+        assert_eq!(budget.required(), 8_639_800);
+        assert_eq!(budget.surplus(), 360_200);
+
+        // 结束之后不再发放
+        let budget = reward_budget(&pool_state, 0, OPEN_TIME + 2 * PERIOD, 0).unwrap();
+        assert_eq!(budget.remaining, 0);
+        assert_eq!(budget.emitted, 500 + 10 * (PERIOD - 50));
+        assert!(budget.surplus() < 0);
+
+        // 没有流动性时不发放
+        pool_state.liquidity = 0;
+        let budget = reward_budget(&pool_state, 0, OPEN_TIME + 100, 0).unwrap();
+        assert_eq!(budget.emitted, 500);
+
+        assert!(reward_budget(&pool_state, 1, OPEN_TIME, 0).is_err());
+        assert!(reward_budget(&pool_state, REWARD_NUM, OPEN_TIME, 0).is_err());
+    }
+
+    #[test]
+    fn apr() {
+        let pool_state = pool_state(1_000_000_000_000);
+        let input = AprInput {
+            tick_lower: -1000,
+            tick_upper: 1000,
+            pool_price: 1.0,
+            reward_price: 2.0,
+            reward_decimals: 6,
+        };
+        let apr = reward_apr(&pool_state, 0, &input, OPEN_TIME).unwrap();
+        assert!(apr > 0.0);
+        // 奖励价格翻倍时 APR 翻倍
+        let doubled = reward_apr(
+            &pool_state,
+            0,
+            &AprInput {
+                reward_price: 4.0,
+                ..input
+            },
+            OPEN_TIME,
+        )
+        .unwrap();
+        assert!((doubled / apr - 2.0).abs() < 1e-9);
+        // 区间越窄,每单位流动性的价值越低,APR 越高
+        let narrow = reward_apr(
+            &pool_state,
+            0,
+            &AprInput {
+                tick_lower: -100,
+                tick_upper: 100,
+                ..input
+            },
+            OPEN_TIME,
+        )
+        .unwrap();
+        assert!(narrow > apr);
+
+        // 价格不在区间内或者奖励没有在发放
+        let out_of_range = AprInput {
+            pool_price: 2.0,
+            ..input
+        };
+        assert_eq!(
+            reward_apr(&pool_state, 0, &out_of_range, OPEN_TIME).unwrap(),
+            0.0
+        );
+        assert_eq!(
+            reward_apr(&pool_state, 0, &input, OPEN_TIME - 1).unwrap(),
+            0.0
+        );
+        assert_eq!(
+            reward_apr(&pool_state, 0, &input, OPEN_TIME + PERIOD).unwrap(),
+            0.0
+        );
+
+        let invalid_ticks = AprInput {
+            tick_lower: 1000,
+            tick_upper: 1000,
+            ..input
+        };
+        assert_eq!(
+            reward_apr(&pool_state, 0, &invalid_ticks, OPEN_TIME).unwrap_err(),
+            ErrorCode::InvaildTickIndex.into()
+        );
+        for (pool_price, reward_price) in [
+            (0.0, 1.0),
+            (-1.0, 1.0),
+            (f64::NAN, 1.0),
+            (f64::INFINITY, 1.0),
+            (1.0, -1.0),
+            (1.0, f64::NAN),
+        ] {
+            let input = AprInput {
+                pool_price,
+                reward_price,
+                ..input
+            };
+            assert_eq!(
+                reward_apr(&pool_state, 0, &input, OPEN_TIME).unwrap_err(),
+                ErrorCode::InvalidPrice.into()
+            );
+        }
+    }
+}
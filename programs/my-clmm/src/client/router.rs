@@ -0,0 +1,612 @@
+//! 链下多池子路由,在一组池子中寻找 1~3 跳的最优交易路径
+//!
+//! 同一对代币可以在不同费率的 AmmConfig 下各有一个池子,很多代币对只能通过中间代币连接。
+//! 路由把池子看作 mint 之间的边,枚举不经过重复 mint 的路径,逐跳用 [`SwapQuoter`] 模拟:
+//! exact-in 从第一跳往后传递输出,exact-out 从最后一跳往前传递需要的输入,
+//! 任何一跳不能完整成交的路径都会被丢弃。
+//!
+//! 程序中还没有 swap 指令,所以这里只给出每一跳需要的账户 [`HopAccounts`],还不能构造 swap 的 `Instruction`,
+//! 等 swap 指令加入后再补上。
+//! 和 [`SwapQuoter`] 一样,报价不包含 Token-2022 的转账手续费
+use super::pda::*;
+use super::quote::{SwapQuote, SwapQuoter};
+use anchor_lang::prelude::*;
+use std::collections::HashMap;
+
+/// 一条路径最多经过的池子数量
+pub const MAX_HOPS: usize = 3;
+
+/// 交易方向: 固定输入或者固定输出
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapMode {
+    ExactIn,
+    ExactOut,
+}
+
+/// 一跳 swap 需要的账户
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HopAccounts {
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub input_vault: Pubkey,
+    pub output_vault: Pubkey,
+    pub observation_state: Pubkey,
+    pub input_vault_mint: Pubkey,
+    pub output_vault_mint: Pubkey,
+    /// remaining accounts 中的第一个账户
+    pub tick_array_bitmap_extension: Pubkey,
+    /// remaining accounts 中 tick array 的 start index,按交易方向排列
+    pub tick_array_start_indexes: Vec<i32>,
+}
+
+/// 路径中的一跳
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteHop {
+    pub pool_state: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub zero_for_one: bool,
+    pub quote: SwapQuote,
+    pub accounts: HopAccounts,
+}
+
+/// 一条完整的路径,前一跳的输出等于后一跳的输入
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub hops: Vec<RouteHop>,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+/// 拆分到多条互不共用池子的路径上的交易
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitRoute {
+    pub routes: Vec<Route>,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+/// 池子图,每个池子是它的两个 mint 之间的一条边
+pub struct PoolGraph {
+    pools: Vec<(Pubkey, SwapQuoter)>,
+    /// mint 到包含它的池子在 `pools` 中的下标
+    edges: HashMap<Pubkey, Vec<usize>>,
+}
+
+impl PoolGraph {
+    pub fn new(quoters: impl IntoIterator<Item = SwapQuoter>) -> Self {
+        let mut pools = vec![];
+        let mut edges: HashMap<Pubkey, Vec<usize>> = HashMap::new();
+        for quoter in quoters {
+            let pool_state = &quoter.pool_state;
+            let (token_mint_0, token_mint_1) =
+                ({ pool_state.token_mint_0 }, { pool_state.token_mint_1 });
+            let address = pool_address(&{ pool_state.amm_config }, &token_mint_0, &token_mint_1).0;
+            edges.entry(token_mint_0).or_default().push(pools.len());
+            edges.entry(token_mint_1).or_default().push(pools.len());
+            pools.push((address, quoter));
+        }
+        Self { pools, edges }
+    }
+
+    /// `input_mint` 和 `output_mint` 之间不超过 `max_hops` 跳的最优路径,
+    /// exact-in 的 `amount` 是输入数量,输出最多的路径最优;exact-out 的 `amount` 是输出数量,输入最少的路径最优。
+    /// 结果相同时跳数少的优先,没有能完整成交的路径时返回 None
+    pub fn best_route(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+        mode: SwapMode,
+        max_hops: usize,
+    ) -> Option<Route> {
+        self.paths(input_mint, output_mint, max_hops)
+            .iter()
+            .filter_map(|path| self.quote_path(path, input_mint, amount, mode))
+            .fold(None, |best, route| match best {
+                Some(best) if !is_better(&route, &best, mode) => Some(best),
+                _ => Some(route),
+            })
+    }
+
+    /// 把交易拆分到最多 `max_splits` 条互不共用池子的路径上,每次把 `amount / parts` 分给边际收益最大的路径。
+    /// 拆分后不比 [`PoolGraph::best_route`] 更好时,只返回最优的那一条路径
+    #[allow(clippy::too_many_arguments)]
+    pub fn best_split_route(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+        mode: SwapMode,
+        max_hops: usize,
+        max_splits: usize,
+        parts: u64,
+    ) -> Option<SplitRoute> {
+        let best = self.best_route(input_mint, output_mint, amount, mode, max_hops)?;
+        let single = SplitRoute {
+            amount_in: best.amount_in,
+            amount_out: best.amount_out,
+            routes: vec![best],
+        };
+        let parts = parts.clamp(1, amount);
+        if max_splits < 2 || parts < 2 {
+            return Some(single);
+        }
+
+        // 按整笔交易的报价从好到坏选出互不共用池子的路径
+        let mut candidates: Vec<(Vec<usize>, Route)> = self
+            .paths(input_mint, output_mint, max_hops)
+            .into_iter()
+            .filter_map(|path| {
+                let route = self.quote_path(&path, input_mint, amount, mode)?;
+                Some((path, route))
+            })
+            .collect();
+        candidates.sort_by(|(_, a), (_, b)| {
+            if is_better(a, b, mode) {
+                std::cmp::Ordering::Less
+            } else if is_better(b, a, mode) {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        });
+        let mut selected: Vec<Vec<usize>> = vec![];
+        for (path, _) in candidates {
+            if selected.len() == max_splits {
+                break;
+            }
+            if selected
+                .iter()
+                .all(|other| other.iter().all(|pool| !path.contains(pool)))
+            {
+                selected.push(path);
+            }
+        }
+        if selected.len() < 2 {
+            return Some(single);
+        }
+
+        // 贪心分配,第一份包含除不尽的部分
+        let chunk = amount / parts;
+        let mut allocated = vec![0u64; selected.len()];
+        let mut routes: Vec<Option<Route>> = vec![None; selected.len()];
+        for i in 0..parts {
+            let size = if i == 0 {
+                chunk + amount % parts
+            } else {
+                chunk
+            };
+            let mut best_choice: Option<(usize, Route, u64)> = None;
+            for (index, path) in selected.iter().enumerate() {
+                let Some(route) = self.quote_path(path, input_mint, allocated[index] + size, mode)
+                else {
+                    continue;
+                };
+                // exact-in 比较增加的输出,exact-out 比较增加的输入的相反数
+                let gain = match (mode, &routes[index]) {
+                    (SwapMode::ExactIn, Some(prev)) => {
+                        route.amount_out.saturating_sub(prev.amount_out)
+                    }
+                    (SwapMode::ExactIn, None) => route.amount_out,
+                    (SwapMode::ExactOut, Some(prev)) => {
+                        u64::MAX - route.amount_in.saturating_sub(prev.amount_in)
+                    }
+                    (SwapMode::ExactOut, None) => u64::MAX - route.amount_in,
+                };
+                if best_choice
+                    .as_ref()
+                    .map_or(true, |(_, _, best_gain)| gain > *best_gain)
+                {
+                    best_choice = Some((index, route, gain));
+                }
+            }
+            let Some((index, route, _)) = best_choice else {
+                return Some(single);
+            };
+            allocated[index] += size;
+            routes[index] = Some(route);
+        }
+
+        let routes: Vec<Route> = routes.into_iter().flatten().collect();
+        let split = SplitRoute {
+            amount_in: routes.iter().map(|route| route.amount_in).sum(),
+            amount_out: routes.iter().map(|route| route.amount_out).sum(),
+            routes,
+        };
+        let split_is_better = match mode {
+            SwapMode::ExactIn => split.amount_out > single.amount_out,
+            SwapMode::ExactOut => split.amount_in < single.amount_in,
+        };
+        Some(if split_is_better { split } else { single })
+    }
+
+    /// 从 `input_mint` 到 `output_mint` 不经过重复 mint 的所有路径,每条路径是池子下标的序列
+    fn paths(&self, input_mint: Pubkey, output_mint: Pubkey, max_hops: usize) -> Vec<Vec<usize>> {
+        let mut paths = vec![];
+        if input_mint != output_mint {
+            let mut visited = vec![input_mint];
+            let mut path = vec![];
+            self.search(
+                input_mint,
+                output_mint,
+                max_hops.min(MAX_HOPS),
+                &mut visited,
+                &mut path,
+                &mut paths,
+            );
+        }
+        paths
+    }
+
+    fn search(
+        &self,
+        mint: Pubkey,
+        output_mint: Pubkey,
+        max_hops: usize,
+        visited: &mut Vec<Pubkey>,
+        path: &mut Vec<usize>,
+        paths: &mut Vec<Vec<usize>>,
+    ) {
+        if path.len() == max_hops {
+            return;
+        }
+        for &index in self.edges.get(&mint).into_iter().flatten() {
+            let next = self.other_mint(index, mint);
+            if visited.contains(&next) {
+                continue;
+            }
+            path.push(index);
+            if next == output_mint {
+                paths.push(path.clone());
+            } else {
+                visited.push(next);
+                self.search(next, output_mint, max_hops, visited, path, paths);
+                visited.pop();
+            }
+            path.pop();
+        }
+    }
+
+    fn other_mint(&self, index: usize, mint: Pubkey) -> Pubkey {
+        let pool_state = &self.pools[index].1.pool_state;
+        if mint == { pool_state.token_mint_0 } {
+            pool_state.token_mint_1
+        } else {
+            pool_state.token_mint_0
+        }
+    }
+
+    /// 沿着 `path` 模拟交易,任何一跳报价失败或者不能完整成交时返回 None
+    fn quote_path(
+        &self,
+        path: &[usize],
+        input_mint: Pubkey,
+        amount: u64,
+        mode: SwapMode,
+    ) -> Option<Route> {
+        // 每一跳的输入 mint
+        let mut input_mints = vec![input_mint];
+        for &index in path {
+            let mint = *input_mints.last().unwrap();
+            input_mints.push(self.other_mint(index, mint));
+        }
+
+        let mut hops: Vec<RouteHop> = Vec::with_capacity(path.len());
+        match mode {
+            SwapMode::ExactIn => {
+                let mut amount_in = amount;
+                for (i, &index) in path.iter().enumerate() {
+                    let hop = self.quote_hop(index, input_mints[i], amount_in, mode)?;
+                    amount_in = hop.quote.amount_out;
+                    hops.push(hop);
+                }
+            }
+            SwapMode::ExactOut => {
+                let mut amount_out = amount;
+                for (i, &index) in path.iter().enumerate().rev() {
+                    let hop = self.quote_hop(index, input_mints[i], amount_out, mode)?;
+                    amount_out = hop.quote.amount_in;
+                    hops.push(hop);
+                }
+                hops.reverse();
+            }
+        }
+        Some(Route {
+            amount_in: hops.first()?.quote.amount_in,
+            amount_out: hops.last()?.quote.amount_out,
+            hops,
+        })
+    }
+
+    fn quote_hop(
+        &self,
+        index: usize,
+        input_mint: Pubkey,
+        amount: u64,
+        mode: SwapMode,
+    ) -> Option<RouteHop> {
+        let (pool_address, quoter) = &self.pools[index];
+        let pool_state = &quoter.pool_state;
+        let zero_for_one = input_mint == { pool_state.token_mint_0 };
+        let quote = match mode {
+            SwapMode::ExactIn => quoter.quote_exact_in(amount, zero_for_one, None).ok()?,
+            SwapMode::ExactOut => quoter.quote_exact_out(amount, zero_for_one, None).ok()?,
+        };
+        let filled = match mode {
+            SwapMode::ExactIn => quote.amount_in == amount,
+            SwapMode::ExactOut => quote.amount_out == amount,
+        };
+        if !filled || quote.amount_out == 0 {
+            return None;
+        }
+
+        let (input_vault, output_vault, output_mint) = if zero_for_one {
+            (
+                pool_state.token_vault_0,
+                pool_state.token_vault_1,
+                pool_state.token_mint_1,
+            )
+        } else {
+            (
+                pool_state.token_vault_1,
+                pool_state.token_vault_0,
+                pool_state.token_mint_0,
+            )
+        };
+        Some(RouteHop {
+            pool_state: *pool_address,
+            input_mint,
+            output_mint,
+            zero_for_one,
+            accounts: HopAccounts {
+                amm_config: pool_state.amm_config,
+                pool_state: *pool_address,
+                input_vault,
+                output_vault,
+                observation_state: pool_state.observation_key,
+                input_vault_mint: input_mint,
+                output_vault_mint: output_mint,
+                tick_array_bitmap_extension: tick_array_bitmap_extension_address(pool_address).0,
+                tick_array_start_indexes: quote.tick_array_start_indexes.clone(),
+            },
+            quote,
+        })
+    }
+}
+
+/// `a` 是否严格优于 `b`,结果相同时跳数少的更好
+fn is_better(a: &Route, b: &Route, mode: SwapMode) -> bool {
+    let ordering = match mode {
+        SwapMode::ExactIn => a.amount_out.cmp(&b.amount_out),
+        SwapMode::ExactOut => b.amount_in.cmp(&a.amount_in),
+    };
+    ordering.then(b.hops.len().cmp(&a.hops.len())).is_gt()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::libraries::fixed_point_64;
+    use crate::states::{AmmConfig, PoolState, TickArrayBitmapExtension};
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+    const POSITION_TICK: i32 = 300_000;
+
+    /// 价格为 1 的池子,`index` 不同的配置对应不同的池子地址,
+    /// 流动性在 [-POSITION_TICK, POSITION_TICK) 范围内
+    fn pool(
+        index: u16,
+        trade_fee_rate: u32,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        liquidity: u128,
+    ) -> SwapQuoter {
+        let (token_mint_0, token_mint_1) = if mint_a < mint_b {
+            (mint_a, mint_b)
+        } else {
+            (mint_b, mint_a)
+        };
+        let amm_config = amm_config_address(index).0;
+        let pool_state = pool_address(&amm_config, &token_mint_0, &token_mint_1).0;
+        let mut quoter = SwapQuoter {
+            pool_state: PoolState {
+                amm_config,
+                token_mint_0,
+                token_mint_1,
+                token_vault_0: pool_vault_address(&pool_state, &token_mint_0).0,
+                token_vault_1: pool_vault_address(&pool_state, &token_mint_1).0,
+                observation_key: observation_address(&pool_state).0,
+                tick_spacing: 10,
+                sqrt_price_x64: fixed_point_64::Q64,
+                tick_current: 0,
+                ..Default::default()
+            },
+            amm_config: AmmConfig {
+                index,
+                trade_fee_rate,
+                tick_spacing: 10,
+                ..Default::default()
+            },
+            tick_array_bitmap_extension: TickArrayBitmapExtension::default(),
+            tick_arrays: Default::default(),
+        };
+        if liquidity > 0 {
+            quoter.add_position(-POSITION_TICK, POSITION_TICK, liquidity);
+        }
+        quoter
+    }
+
+    fn mints() -> [Pubkey; 4] {
+        [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ]
+    }
+
+    #[test]
+    fn picks_cheapest_fee_tier() {
+        let [a, b, ..] = mints();
+        let graph = PoolGraph::new([
+            pool(0, 10_000, a, b, LIQUIDITY),
+            pool(1, 500, a, b, LIQUIDITY),
+            pool(2, 2500, a, b, LIQUIDITY),
+        ]);
+        let route = graph
+            .best_route(a, b, 1_000_000, SwapMode::ExactIn, MAX_HOPS)
+            .unwrap();
+        assert_eq!(route.hops.len(), 1);
+        let hop = &route.hops[0];
+        assert_eq!(hop.accounts.amm_config, amm_config_address(1).0);
+        assert_eq!(hop.input_mint, a);
+        assert_eq!(hop.output_mint, b);
+        assert_eq!(hop.zero_for_one, a < b);
+        assert_eq!(
+            hop.accounts.input_vault,
+            pool_vault_address(&hop.pool_state, &a).0
+        );
+        assert_eq!(
+            hop.accounts.output_vault,
+            pool_vault_address(&hop.pool_state, &b).0
+        );
+        assert_eq!(
+            hop.accounts.tick_array_bitmap_extension,
+            tick_array_bitmap_extension_address(&hop.pool_state).0
+        );
+        // tick 0 所在的 tick array 没有初始化,交易从 position 边界所在的 tick array 开始
+        let tick_arrays = if hop.zero_for_one {
+            vec![-POSITION_TICK]
+        } else {
+            vec![POSITION_TICK]
+        };
+        assert_eq!(hop.accounts.tick_array_start_indexes, tick_arrays);
+        assert_eq!(route.amount_in, 1_000_000);
+        assert_eq!(route.amount_out, hop.quote.amount_out);
+    }
+
+    #[test]
+    fn routes_through_hub() {
+        let [a, b, hub, other] = mints();
+        // 直连的池子流动性很少,大额交易走中间代币更好
+        let graph = PoolGraph::new([
+            pool(0, 2500, a, b, LIQUIDITY / 1000),
+            pool(0, 500, a, hub, LIQUIDITY),
+            pool(0, 500, hub, b, LIQUIDITY),
+            pool(0, 500, hub, other, LIQUIDITY),
+        ]);
+        let route = graph
+            .best_route(a, b, 1_000_000_000, SwapMode::ExactIn, MAX_HOPS)
+            .unwrap();
+        assert_eq!(route.hops.len(), 2);
+        assert_eq!(route.hops[0].output_mint, hub);
+        assert_eq!(route.hops[1].input_mint, hub);
+        assert_eq!(
+            route.hops[0].quote.amount_out,
+            route.hops[1].quote.amount_in
+        );
+        assert_eq!(route.amount_out, route.hops[1].quote.amount_out);
+
+        // 限制为一跳时只能走直连的池子
+        let direct = graph
+            .best_route(a, b, 1_000_000_000, SwapMode::ExactIn, 1)
+            .unwrap();
+        assert_eq!(direct.hops.len(), 1);
+        assert!(direct.amount_out < route.amount_out);
+
+        // a 和 other 之间没有直连的池子
+        assert!(graph
+            .best_route(a, other, 1_000, SwapMode::ExactIn, 1)
+            .is_none());
+        let route = graph
+            .best_route(a, other, 1_000, SwapMode::ExactIn, MAX_HOPS)
+            .unwrap();
+        assert_eq!(route.hops.len(), 2);
+    }
+
+    #[test]
+    fn exact_out_chains_backwards() {
+        let [a, b, hub, _] = mints();
+        let graph = PoolGraph::new([
+            pool(0, 2500, a, hub, LIQUIDITY),
+            pool(0, 2500, hub, b, LIQUIDITY),
+        ]);
+        let route = graph
+            .best_route(a, b, 1_000_000, SwapMode::ExactOut, MAX_HOPS)
+            .unwrap();
+        assert_eq!(route.amount_out, 1_000_000);
+        assert_eq!(route.hops[1].quote.amount_out, 1_000_000);
+        assert_eq!(
+            route.hops[0].quote.amount_out,
+            route.hops[1].quote.amount_in
+        );
+        assert_eq!(route.amount_in, route.hops[0].quote.amount_in);
+
+        // 按 exact-out 的输入做 exact-in,得到的输出不会少于要求的输出
+        let exact_in = graph
+            .best_route(a, b, route.amount_in, SwapMode::ExactIn, MAX_HOPS)
+            .unwrap();
+        assert!(exact_in.amount_out >= 1_000_000);
+    }
+
+    #[test]
+    fn no_route() {
+        let [a, b, c, d] = mints();
+        let graph = PoolGraph::new([
+            pool(0, 2500, a, b, LIQUIDITY),
+            pool(0, 2500, c, d, LIQUIDITY),
+        ]);
+        assert!(graph
+            .best_route(a, d, 1_000, SwapMode::ExactIn, MAX_HOPS)
+            .is_none());
+        assert!(graph
+            .best_route(a, a, 1_000, SwapMode::ExactIn, MAX_HOPS)
+            .is_none());
+        // 没有流动性的池子不能成交
+        let graph = PoolGraph::new([pool(0, 2500, a, b, 0)]);
+        assert!(graph
+            .best_route(a, b, 1_000, SwapMode::ExactIn, MAX_HOPS)
+            .is_none());
+        assert!(graph
+            .best_route(a, b, 1_000, SwapMode::ExactOut, MAX_HOPS)
+            .is_none());
+    }
+
+    #[test]
+    fn split_across_parallel_pools() {
+        let [a, b, ..] = mints();
+        let graph = PoolGraph::new([
+            pool(0, 2500, a, b, LIQUIDITY),
+            pool(1, 2500, a, b, LIQUIDITY),
+        ]);
+        // 交易量和流动性相当,拆分到两个池子可以减少价格冲击
+        let amount = 100_000_000_000;
+        let single = graph
+            .best_route(a, b, amount, SwapMode::ExactIn, MAX_HOPS)
+            .unwrap();
+        let split = graph
+            .best_split_route(a, b, amount, SwapMode::ExactIn, MAX_HOPS, 2, 10)
+            .unwrap();
+        assert_eq!(split.routes.len(), 2);
+        assert_eq!(split.amount_in, amount);
+        assert!(split.amount_out > single.amount_out);
+        assert_eq!(split.routes[0].amount_in, amount / 2);
+        assert_ne!(
+            split.routes[0].hops[0].pool_state,
+            split.routes[1].hops[0].pool_state
+        );
+
+        let split_out = graph
+            .best_split_route(a, b, single.amount_out, SwapMode::ExactOut, MAX_HOPS, 2, 10)
+            .unwrap();
+        assert_eq!(split_out.amount_out, single.amount_out);
+        assert!(split_out.amount_in < amount);
+
+        // 小额交易拆分没有好处
+        let small = graph
+            .best_split_route(a, b, 1_000, SwapMode::ExactIn, MAX_HOPS, 2, 10)
+            .unwrap();
+        assert_eq!(small.routes.len(), 1);
+    }
+}
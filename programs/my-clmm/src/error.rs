@@ -105,4 +105,22 @@ pub enum ErrorCode {
     MaxTokenOverflow,
     #[msg("calculate overflow")]
     CalculateOverflow,
+    #[msg("The amm config is disabled for creating new pools")]
+    AmmConfigDisabled,
+    #[msg("The operation account reach to the max capacity")]
+    OperationCapacityExhausted,
+    #[msg("Invalid operation role")]
+    InvalidOperationRole,
+    #[msg("trade_fee_rate must be less than FEE_RATE_DENOMINATOR_VALUE")]
+    InvalidTradeFeeRate,
+    #[msg("protocol_fee_rate must not exceed FEE_RATE_DENOMINATOR_VALUE")]
+    InvalidProtocolFeeRate,
+    #[msg("fund_fee_rate must not exceed FEE_RATE_DENOMINATOR_VALUE")]
+    InvalidFundFeeRate,
+    #[msg("protocol_fee_rate + fund_fee_rate must not exceed FEE_RATE_DENOMINATOR_VALUE")]
+    InvalidFeeRateSum,
+    #[msg("TransferFee calculate not match")]
+    TransferFeeCalculateNotMatch,
+    #[msg("Price must be finite and positive")]
+    InvalidPrice,
 }
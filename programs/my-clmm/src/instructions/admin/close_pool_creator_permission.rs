@@ -0,0 +1,31 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClosePoolCreatorPermission<'info> {
+    /// Only admin can revoke the permission
+    #[account(
+        mut,
+        address = crate::admin::id() @ ErrorCode::NotApproved
+    )]
+    pub owner: Signer<'info>,
+
+    /// The permission to be revoked, the rent is returned to the owner
+    #[account(
+        mut,
+        seeds = [
+            POOL_CREATOR_PERMISSION_SEED.as_bytes(),
+            pool_creator_permission.amm_config.as_ref(),
+            pool_creator_permission.creator.as_ref(),
+        ],
+        bump = pool_creator_permission.bump,
+        close = owner
+    )]
+    pub pool_creator_permission: Account<'info, PoolCreatorPermission>,
+}
+
+/// 撤销创建者的权限,已经创建的池子不受影响
+pub fn close_pool_creator_permission(_ctx: Context<ClosePoolCreatorPermission>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseSupportMintAssociated<'info> {
+    /// The admin or an operation owner with whitelist manager role
+    #[account(
+        mut,
+        constraint = (authority.key() == crate::admin::id()
+            || operation_state.validate_operation_owner(authority.key(), operation_role::WHITELIST_MANAGER)) @ ErrorCode::NotApproved
+    )]
+    pub authority: Signer<'info>,
+
+    /// load info from the account to judge the whitelist manager role
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: Box<Account<'info, OperationState>>,
+
+    /// The record to be removed from the whitelist, the rent is returned to the authority
+    #[account(
+        mut,
+        seeds = [
+            SUPPORT_MINT_SEED.as_bytes(),
+            support_mint_associated.mint.as_ref(),
+        ],
+        bump = support_mint_associated.bump,
+        close = authority
+    )]
+    pub support_mint_associated: Account<'info, SupportMintAssociated>,
+}
+
+/// 将 mint 从链上白名单中移除,已经创建的池子和奖励不受影响
+pub fn close_support_mint_associated(_ctx: Context<CloseSupportMintAssociated>) -> Result<()> {
+    Ok(())
+}
@@ -37,6 +37,7 @@ pub fn create_amm_config(
     protocol_fee_rate: u32,
     fund_fee_rate: u32,
 ) -> Result<()> {
+    AmmConfig::check_fee_rates(trade_fee_rate, protocol_fee_rate, fund_fee_rate)?;
     let amm_config = ctx.accounts.amm_config.deref_mut();
     amm_config.owner = ctx.accounts.owner.key();
     amm_config.bump = ctx.bumps.amm_config;
@@ -55,6 +56,7 @@ pub fn create_amm_config(
         tick_spacing: amm_config.tick_spacing,
         fund_fee_rate: amm_config.fund_fee_rate,
         fund_owner: amm_config.fund_owner,
+        status: amm_config.status,
     });
 
     Ok(())
@@ -20,13 +20,14 @@ pub struct CreateOperationAccount<'info> {
         payer = owner,
         space = OperationState::LEN
     )]
-    pub operation_state: AccountLoader<'info, OperationState>,
+    pub operation_state: Account<'info, OperationState>,
 
     pub system_program: Program<'info, System>,
 }
 
 pub fn create_operation_account(ctx: Context<CreateOperationAccount>) -> Result<()> {
-    let mut operation_state = ctx.accounts.operation_state.load_init()?;
-    operation_state.initialize(ctx.bumps.operation_state);
+    ctx.accounts
+        .operation_state
+        .initialize(ctx.bumps.operation_state);
     Ok(())
 }
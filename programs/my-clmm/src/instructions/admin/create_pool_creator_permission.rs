@@ -0,0 +1,45 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreatePoolCreatorPermission<'info> {
+    /// Only admin can grant the permission
+    #[account(
+        mut,
+        address = crate::admin::id() @ ErrorCode::NotApproved
+    )]
+    pub owner: Signer<'info>,
+
+    /// The config which the permission belongs to
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// CHECK: The creator to be granted, can be any address
+    pub creator: UncheckedAccount<'info>,
+
+    /// Initialize an account to store the permission of the creator
+    #[account(
+        init,
+        seeds = [
+            POOL_CREATOR_PERMISSION_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            creator.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = PoolCreatorPermission::LEN
+    )]
+    pub pool_creator_permission: Account<'info, PoolCreatorPermission>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 授权某个创建者可以在需要权限的配置下创建池子
+pub fn create_pool_creator_permission(ctx: Context<CreatePoolCreatorPermission>) -> Result<()> {
+    ctx.accounts.pool_creator_permission.initialize(
+        ctx.bumps.pool_creator_permission,
+        ctx.accounts.amm_config.key(),
+        ctx.accounts.creator.key(),
+    );
+    Ok(())
+}
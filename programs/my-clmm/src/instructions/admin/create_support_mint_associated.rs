@@ -0,0 +1,60 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+#[derive(Accounts)]
+pub struct CreateSupportMintAssociated<'info> {
+    /// The admin or an operation owner with whitelist manager role
+    #[account(
+        mut,
+        constraint = (authority.key() == crate::admin::id()
+            || operation_state.validate_operation_owner(authority.key(), operation_role::WHITELIST_MANAGER)) @ ErrorCode::NotApproved
+    )]
+    pub authority: Signer<'info>,
+
+    /// load info from the account to judge the whitelist manager role
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: Box<Account<'info, OperationState>>,
+
+    /// The reviewed mint
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Initialize an account to record the reviewed extensions of the mint
+    #[account(
+        init,
+        seeds = [
+            SUPPORT_MINT_SEED.as_bytes(),
+            token_mint.key().as_ref(),
+        ],
+        bump,
+        payer = authority,
+        space = SupportMintAssociated::LEN
+    )]
+    pub support_mint_associated: Account<'info, SupportMintAssociated>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 将 mint 加入链上白名单,并记录 mint 当前带有的扩展作为审核过的扩展
+pub fn create_support_mint_associated(ctx: Context<CreateSupportMintAssociated>) -> Result<()> {
+    let extensions = util::get_mint_extension_types(&ctx.accounts.token_mint)?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "support mint:{}, reviewed extensions:{:?}",
+        ctx.accounts.token_mint.key(),
+        extensions
+    );
+    ctx.accounts.support_mint_associated.initialize(
+        ctx.bumps.support_mint_associated,
+        ctx.accounts.token_mint.key(),
+        &extensions,
+    );
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::{close_account, realloc_account_if_needed};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MigrateOperationAccount<'info> {
+    /// Only admin can migrate, pays for the new account and receives the rent of the legacy one
+    #[account(
+        mut,
+        address = crate::admin::id() @ ErrorCode::NotApproved
+    )]
+    pub owner: Signer<'info>,
+
+    /// CHECK: The legacy zero copy operation account, its layout is checked in the instruction
+    #[account(
+        mut,
+        seeds = [
+            LEGACY_OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+        owner = crate::id(),
+    )]
+    pub legacy_operation_state: UncheckedAccount<'info>,
+
+    /// The new operation account, initialized with the owners and whitelist mints of the legacy one
+    #[account(
+        init,
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = OperationState::LEN
+    )]
+    pub operation_state: Account<'info, OperationState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 把旧版本 zero_copy 布局的运营账户迁移到新的账户,迁移后关闭旧账户
+pub fn migrate_operation_account(ctx: Context<MigrateOperationAccount>) -> Result<()> {
+    let legacy_operation_state = ctx.accounts.legacy_operation_state.to_account_info();
+    let operation_state = &mut ctx.accounts.operation_state;
+    operation_state.migrate_from_legacy(
+        ctx.bumps.operation_state,
+        &legacy_operation_state.try_borrow_data()?,
+    )?;
+
+    let new_len = operation_state.current_space();
+    realloc_account_if_needed(
+        &operation_state.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+    close_account(
+        &legacy_operation_state,
+        &ctx.accounts.owner.to_account_info(),
+    )
+}
@@ -12,3 +12,21 @@ pub use update_operation_account::*;
 
 pub mod transfer_reward_owner;
 pub use transfer_reward_owner::*;
+
+pub mod update_amm_config_status;
+pub use update_amm_config_status::*;
+
+pub mod create_pool_creator_permission;
+pub use create_pool_creator_permission::*;
+
+pub mod close_pool_creator_permission;
+pub use close_pool_creator_permission::*;
+
+pub mod create_support_mint_associated;
+pub use create_support_mint_associated::*;
+
+pub mod close_support_mint_associated;
+pub use close_support_mint_associated::*;
+
+pub mod migrate_operation_account;
+pub use migrate_operation_account::*;
@@ -12,22 +12,52 @@ pub struct UpdateAmmConfig<'info> {
     pub amm_config: Account<'info, AmmConfig>,
 }
 
-pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u32) -> Result<()> {
+/// 更新 AMM 配置的参数,每个变体对应一种更新操作
+#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Debug, PartialEq)]
+pub enum AmmConfigUpdate {
+    /// 新的交易费率
+    TradeFeeRate(u32),
+    /// 新的协议费率
+    ProtocolFeeRate(u32),
+    /// 新的基金费率
+    FundFeeRate(u32),
+    /// 新的所有者
+    Owner(Pubkey),
+    /// 新的基金所有者
+    FundOwner(Pubkey),
+}
+
+pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, update: AmmConfigUpdate) -> Result<()> {
     let amm_config = &mut ctx.accounts.amm_config;
-    let match_param = Some(param);
-    match match_param {
-        Some(0) => update_trade_fee_rate(amm_config, value),
-        Some(1) => update_protocol_fee_rate(amm_config, value),
-        Some(2) => update_fund_fee_rate(amm_config, value),
-        Some(3) => {
-            let new_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
-            set_new_owner(amm_config, new_owner);
+    match update {
+        AmmConfigUpdate::TradeFeeRate(trade_fee_rate) => {
+            AmmConfig::check_fee_rates(
+                trade_fee_rate,
+                amm_config.protocol_fee_rate,
+                amm_config.fund_fee_rate,
+            )?;
+            amm_config.trade_fee_rate = trade_fee_rate;
+        }
+        AmmConfigUpdate::ProtocolFeeRate(protocol_fee_rate) => {
+            AmmConfig::check_fee_rates(
+                amm_config.trade_fee_rate,
+                protocol_fee_rate,
+                amm_config.fund_fee_rate,
+            )?;
+            amm_config.protocol_fee_rate = protocol_fee_rate;
         }
-        Some(4) => {
-            let new_fund_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
-            set_new_fund_owner(amm_config, new_fund_owner);
+        AmmConfigUpdate::FundFeeRate(fund_fee_rate) => {
+            AmmConfig::check_fee_rates(
+                amm_config.trade_fee_rate,
+                amm_config.protocol_fee_rate,
+                fund_fee_rate,
+            )?;
+            amm_config.fund_fee_rate = fund_fee_rate;
+        }
+        AmmConfigUpdate::Owner(new_owner) => set_new_owner(amm_config, new_owner),
+        AmmConfigUpdate::FundOwner(new_fund_owner) => {
+            set_new_fund_owner(amm_config, new_fund_owner)
         }
-        _ => return err!(ErrorCode::InvalidUpdateConfigFlag),
     }
 
     emit!(ConfigChangeEvent {
@@ -38,28 +68,12 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u32) -
         tick_spacing: amm_config.tick_spacing,
         fund_fee_rate: amm_config.fund_fee_rate,
         fund_owner: amm_config.fund_owner,
+        status: amm_config.status,
     });
 
     Ok(())
 }
 
-fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u32) {
-    assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    amm_config.protocol_fee_rate = protocol_fee_rate;
-}
-
-fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u32) {
-    assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
-    amm_config.trade_fee_rate = trade_fee_rate;
-}
-
-fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u32) {
-    assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    amm_config.fund_fee_rate = fund_fee_rate;
-}
-
 fn set_new_owner(amm_config: &mut Account<AmmConfig>, new_owner: Pubkey) {
     #[cfg(feature = "enable-log")]
     msg!(
@@ -0,0 +1,34 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateAmmConfigStatus<'info> {
+    /// The amm config owner or admin
+    #[account(
+        constraint = (owner.key() == amm_config.owner || owner.key() == crate::admin::id()) @ ErrorCode::NotApproved
+    )]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+/// 更新配置的状态,只影响之后新建的池子,已存在的池子不受影响
+pub fn update_amm_config_status(ctx: Context<UpdateAmmConfigStatus>, status: u8) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.set_status(status);
+
+    emit!(ConfigChangeEvent {
+        index: amm_config.index,
+        owner: amm_config.owner,
+        trade_fee_rate: amm_config.trade_fee_rate,
+        protocol_fee_rate: amm_config.protocol_fee_rate,
+        tick_spacing: amm_config.tick_spacing,
+        fund_fee_rate: amm_config.fund_fee_rate,
+        fund_owner: amm_config.fund_owner,
+        status: amm_config.status,
+    });
+
+    Ok(())
+}
@@ -1,13 +1,13 @@
 use crate::error::ErrorCode;
 use crate::states::*;
+use crate::util::realloc_account_if_needed;
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct UpdateOperationAccount<'info> {
-    /// Address to be set as operation account owner.
-    #[account(
-        address = crate::admin::id() @ ErrorCode::NotApproved
-    )]
+    /// The admin, or an operation owner with whitelist manager role when updating whitelist mints.
+    /// Pays for the rent when the operation account grows.
+    #[account(mut)]
     pub owner: Signer<'info>,
 
     /// Initialize operation state account to store operation owner address and white list mint.
@@ -18,7 +18,7 @@ pub struct UpdateOperationAccount<'info> {
         ],
         bump,
     )]
-    pub operation_state: AccountLoader<'info, OperationState>,
+    pub operation_state: Account<'info, OperationState>,
 
     pub system_program: Program<'info, System>,
 }
@@ -27,15 +27,41 @@ pub fn update_operation_account(
     ctx: Context<UpdateOperationAccount>,
     param: u8,
     keys: Vec<Pubkey>,
+    roles: u8,
 ) -> Result<()> {
-    let mut operation_state = ctx.accounts.operation_state.load_mut()?;
+    let owner = ctx.accounts.owner.key();
+    let operation_state = &mut ctx.accounts.operation_state;
+    // 只有admin可以增删运营人员, 白名单还可以由拥有 WHITELIST_MANAGER 角色的运营人员管理
+    let is_admin = owner == crate::admin::id();
+    let is_whitelist_manager = is_admin
+        || operation_state.validate_operation_owner(owner, operation_role::WHITELIST_MANAGER);
     let match_param = Some(param);
     match match_param {
-        Some(0) => operation_state.update_operation_owner(keys),
-        Some(1) => operation_state.remove_operation_owner(keys),
-        Some(2) => operation_state.update_whitelist_mint(keys),
-        Some(3) => operation_state.remove_whitelist_mint(keys),
+        Some(0) => {
+            require!(is_admin, ErrorCode::NotApproved);
+            operation_state.update_operation_owner(keys, roles)?
+        }
+        Some(1) => {
+            require!(is_admin, ErrorCode::NotApproved);
+            operation_state.remove_operation_owner(keys)
+        }
+        Some(2) => {
+            require!(is_whitelist_manager, ErrorCode::NotApproved);
+            operation_state.update_whitelist_mint(keys)?
+        }
+        Some(3) => {
+            require!(is_whitelist_manager, ErrorCode::NotApproved);
+            operation_state.remove_whitelist_mint(keys)
+        }
         _ => return err!(ErrorCode::InvalidUpdateConfigFlag),
     }
-    Ok(())
+
+    // 扩容以容纳新增的运营人员或白名单 mint
+    let new_len = operation_state.current_space();
+    realloc_account_if_needed(
+        &operation_state.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )
 }
@@ -107,13 +107,67 @@ pub struct CreatePool<'info> {
     pub system_program: Program<'info, System>,
     /// Sysvar for program account
     pub rent: Sysvar<'info, Rent>,
+
+    /// The permission of pool creator, only required when the config is permissioned
+    #[account(
+        seeds = [
+            POOL_CREATOR_PERMISSION_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            pool_creator.key().as_ref(),
+        ],
+        bump = pool_creator_permission.bump,
+    )]
+    pub pool_creator_permission: Option<Box<Account<'info, PoolCreatorPermission>>>,
+
+    /// The on-chain whitelist record of token_mint_0, only required when the mint has extensions
+    /// not supported by default
+    #[account(
+        seeds = [
+            SUPPORT_MINT_SEED.as_bytes(),
+            token_mint_0.key().as_ref(),
+        ],
+        bump = support_mint_associated_0.bump,
+    )]
+    pub support_mint_associated_0: Option<Box<Account<'info, SupportMintAssociated>>>,
+
+    /// The on-chain whitelist record of token_mint_1
+    #[account(
+        seeds = [
+            SUPPORT_MINT_SEED.as_bytes(),
+            token_mint_1.key().as_ref(),
+        ],
+        bump = support_mint_associated_1.bump,
+    )]
+    pub support_mint_associated_1: Option<Box<Account<'info, SupportMintAssociated>>>,
 }
 
 pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u64) -> Result<()> {
+    // 被禁用的配置不允许再创建新池子
+    require!(
+        !ctx.accounts.amm_config.is_create_pool_disabled(),
+        ErrorCode::AmmConfigDisabled
+    );
+    // 需要权限的配置只允许被授权的创建者创建池子
+    if ctx.accounts.amm_config.is_create_pool_permissioned() {
+        require!(
+            ctx.accounts.pool_creator_permission.is_some(),
+            ErrorCode::NotApproved
+        );
+    }
     // 检查代币是否支持
-    if !(util::is_supported_mint(&ctx.accounts.token_mint_0).unwrap()
-        && util::is_supported_mint(&ctx.accounts.token_mint_1).unwrap())
-    {
+    if !(util::is_supported_mint(
+        &ctx.accounts.token_mint_0,
+        ctx.accounts
+            .support_mint_associated_0
+            .as_deref()
+            .map(|item| &**item),
+    )? && util::is_supported_mint(
+        &ctx.accounts.token_mint_1,
+        ctx.accounts
+            .support_mint_associated_1
+            .as_deref()
+            .map(|item| &**item),
+    )?) {
         return err!(ErrorCode::NotSupportMint);
     }
     let pool_id = ctx.accounts.pool_state.key();
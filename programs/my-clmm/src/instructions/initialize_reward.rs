@@ -1,4 +1,4 @@
-use crate::libraries::{fixed_point_64, full_math::MulDiv, U256};
+use crate::libraries::Q64x64;
 use crate::util::transfer_from_user_to_pool_vault;
 use crate::{states::*, util};
 use anchor_lang::prelude::*;
@@ -34,30 +34,43 @@ pub struct InitializeReward<'info> {
         ],
         bump,
     )]
-    pub operation_state: AccountLoader<'info, OperationState>,
+    pub operation_state: Box<Account<'info, OperationState>>,
 
-    /// Reward mint
+    /// Reward mint, must not be used by another reward of this pool
+    #[account(
+        constraint = !{ pool_state.load()?.reward_infos }
+            .iter()
+            .any(|info| { info.token_mint } == reward_token_mint.key())
+            @ ErrorCode::RewardTokenAlreadyInUse
+    )]
     pub reward_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// A pda, reward vault
+    /// CHECK: A pda, reward vault, created in the instruction after the reward mint is checked
     #[account(
-        init,
+        mut,
         seeds =[
             POOL_REWARD_VAULT_SEED.as_bytes(),
             pool_state.key().as_ref(),
             reward_token_mint.key().as_ref(),
         ],
         bump,
-        payer = reward_funder,
-        token::mint = reward_token_mint,
-        token::authority = pool_state,
-        token::token_program = reward_token_program,
     )]
-    pub reward_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub reward_token_vault: UncheckedAccount<'info>,
 
     pub reward_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// The on-chain whitelist record of reward mint, only required when the mint has extensions
+    /// not supported by default
+    #[account(
+        seeds = [
+            SUPPORT_MINT_SEED.as_bytes(),
+            reward_token_mint.key().as_ref(),
+        ],
+        bump = support_mint_associated.bump,
+    )]
+    pub support_mint_associated: Option<Box<Account<'info, SupportMintAssociated>>>,
 }
 
 /// 初始化奖励参数
@@ -80,7 +93,10 @@ impl InitializeRewardParam {
         {
             return Err(ErrorCode::InvalidRewardInitParam.into());
         }
-        let time_delta = self.end_time.checked_sub(self.open_time).unwrap();
+        let time_delta = self
+            .end_time
+            .checked_sub(self.open_time)
+            .ok_or(ErrorCode::CalculateOverflow)?;
         if time_delta < reward_period_limit::MIN_REWARD_PERIOD
             || time_delta > reward_period_limit::MAX_REWARD_PERIOD
         {
@@ -95,18 +111,27 @@ pub fn initialize_reward(
     param: InitializeRewardParam,
 ) -> Result<()> {
     //判断reward代币是否是支持的mint
-    if !util::is_supported_mint(&ctx.accounts.reward_token_mint).unwrap() {
+    if !util::is_supported_mint(
+        &ctx.accounts.reward_token_mint,
+        ctx.accounts
+            .support_mint_associated
+            .as_deref()
+            .map(|item| &**item),
+    )? {
         return err!(ErrorCode::NotSupportMint);
     }
-    let operation_state = ctx.accounts.operation_state.load()?;
+    let operation_state = &ctx.accounts.operation_state;
     //reward_funder需要是:
     //1.admin
     //2.pool_state的owner
-    //3.operation_owner之一
+    //3.拥有 REWARD_MANAGER 角色的运营人员
     require!(
         ctx.accounts.reward_funder.key() == crate::admin::id()
             || ctx.accounts.reward_funder.key() == ctx.accounts.pool_state.load()?.owner
-            || operation_state.validate_operation_owner(ctx.accounts.reward_funder.key()),
+            || operation_state.validate_operation_owner(
+                ctx.accounts.reward_funder.key(),
+                operation_role::REWARD_MANAGER
+            ),
         ErrorCode::NotApproved
     );
 
@@ -116,28 +141,41 @@ pub fn initialize_reward(
     msg!("current block timestamp:{}", clock.unix_timestamp);
     param.check(clock.unix_timestamp as u64)?;
 
-    let reward_amount = U256::from(param.end_time - param.open_time)
-        .mul_div_ceil(
-            //mul_div_ceil 执行乘法然后除法，并向上取整 使用u258避免溢出
-            U256::from(param.emissions_per_second_x64),
-            //fixed_point_64::Q64 是一个常量，值为 2^64
-            //因为emissions_per_second_x64是Q64.64，所以需要除以Q64(2^64)来变回正常的价格
-            U256::from(fixed_point_64::Q64),
-        )
-        .unwrap()
-        .as_u64();
+    // 奖励总量 = 每秒奖励数量 * 奖励时长,向上取整保证 vault 中的代币足够发放
+    let reward_amount = Q64x64::from_bits(param.emissions_per_second_x64)
+        .checked_mul(Q64x64::from_int(param.end_time - param.open_time))
+        .ok_or(ErrorCode::MaxTokenOverflow)?
+        .ceil_u64()
+        .ok_or(ErrorCode::MaxTokenOverflow)?;
     // 计算转账手续费,如果是Token-2022，则需要计算反向手续费
     let reward_amount_with_transfer_fee = reward_amount
-        .checked_add(
-            util::get_transfer_inverse_fee(ctx.accounts.reward_token_mint.clone(), reward_amount)
-                .unwrap(),
-        )
-        .unwrap();
+        .checked_add(util::get_transfer_inverse_fee(
+            ctx.accounts.reward_token_mint.clone(),
+            reward_amount,
+        )?)
+        .ok_or(ErrorCode::MaxTokenOverflow)?;
     require_gte!(
         ctx.accounts.funder_token_account.amount,
         reward_amount_with_transfer_fee
     );
 
+    let pool_state_key = ctx.accounts.pool_state.key();
+    let reward_token_mint_key = ctx.accounts.reward_token_mint.key();
+    util::create_token_account(
+        &ctx.accounts.pool_state.to_account_info(),
+        &ctx.accounts.reward_funder.to_account_info(),
+        &ctx.accounts.reward_token_vault.to_account_info(),
+        &ctx.accounts.reward_token_mint,
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.reward_token_program.to_account_info(),
+        &[
+            POOL_REWARD_VAULT_SEED.as_bytes(),
+            pool_state_key.as_ref(),
+            reward_token_mint_key.as_ref(),
+            &[ctx.bumps.reward_token_vault],
+        ],
+    )?;
+
     let mut pool_state = ctx.accounts.pool_state.load_mut()?;
     pool_state.initialize_reward(
         param.open_time,
@@ -146,7 +184,7 @@ pub fn initialize_reward(
         &ctx.accounts.reward_token_mint.key(),
         &ctx.accounts.reward_token_vault.key(),
         &ctx.accounts.reward_funder.key(),
-        &operation_state,
+        operation_state,
     )?;
 
     // 从资金提供者转账到pool_vault
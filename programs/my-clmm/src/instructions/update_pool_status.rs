@@ -1,15 +1,30 @@
+use crate::error::ErrorCode;
 use crate::states::*;
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct UpdatePoolStatus<'info> {
+    /// The admin or an operation owner with pool pauser role
     #[account(
-        address = crate::admin::id()
+        constraint = (authority.key() == crate::admin::id()
+            || operation_state.as_ref().map_or(false, |operation_state| {
+                operation_state.validate_operation_owner(authority.key(), operation_role::POOL_PAUSER)
+            })) @ ErrorCode::NotApproved
     )]
     pub authority: Signer<'info>,
 
     #[account(mut)]
     pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// load info from the account to judge the pool pauser role, only required when the authority
+    /// is not the admin
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: Option<Box<Account<'info, OperationState>>>,
 }
 
 pub fn update_pool_status(ctx: Context<UpdatePoolStatus>, status: u8) -> Result<()> {
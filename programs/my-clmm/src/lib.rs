@@ -1,19 +1,31 @@
 use anchor_lang::prelude::*;
-mod error;
-mod instructions;
-mod libraries;
-mod states;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod error;
+pub mod instructions;
+pub mod libraries;
+pub mod states;
 mod util;
 use core as core_;
 use instructions::*;
-use states::*;
 declare_id!("3EJpuBuuaKJH8B6AaFyVinPszYFkYrkiR7FDKDpAzMWh");
 
 pub mod admin {
     use anchor_lang::prelude::declare_id;
+    #[cfg(not(feature = "test-admin"))]
     declare_id!("9qMknujRc8eqBZ6gSrypjYyzNNpwiwASxocKdAfg563C");
+    /// 集成测试使用的 admin,私钥由 `keypair_from_seed(&[42; 32])` 生成
+    #[cfg(feature = "test-admin")]
+    declare_id!("2iXtA8oeZqUU5pofxK971TCEvFGfems2AcDRaZHKD2pQ");
 }
 
+#[cfg(all(
+    feature = "test-admin",
+    target_os = "solana",
+    not(feature = "test-admin-sbf")
+))]
+compile_error!("the test-admin feature must not be enabled for on-chain builds");
+
 #[program]
 pub mod my_clmm {
     use super::*;
@@ -35,10 +47,6 @@ pub mod my_clmm {
         protocol_fee_rate: u32,
         fund_fee_rate: u32,
     ) -> Result<()> {
-        assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
-        assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-        assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-        assert!(fund_fee_rate + protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
         instructions::create_amm_config(
             ctx,
             index,
@@ -55,14 +63,12 @@ pub mod my_clmm {
     /// # 参数
     ///
     /// * `ctx` - 账户上下文
-    /// * `trade_fee_rate` - AMM 配置的新交易费率，当 `param` 为 0 时设置
-    /// * `protocol_fee_rate` - AMM 配置的新协议费率，当 `param` 为 1 时设置
-    /// * `fund_fee_rate` - AMM 配置的新基金费率，当 `param` 为 2 时设置
-    /// * `new_owner` - 配置的新所有者，当 `param` 为 3 时设置
-    /// * `new_fund_owner` - 配置的新基金所有者，当 `param` 为 4 时设置
-    /// * `param` - 取值可以是 0 | 1 | 2 | 3 | 4，其他值将报错
-    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u32) -> Result<()> {
-        instructions::update_amm_config(ctx, param, value)
+    /// * `update` - 要更新的字段及新的值:
+    ///   `TradeFeeRate` 新交易费率, `ProtocolFeeRate` 新协议费率, `FundFeeRate` 新基金费率,
+    ///   `Owner` 配置的新所有者, `FundOwner` 配置的新基金所有者,
+    ///   费率不合法时返回对应的 `ErrorCode`
+    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, update: AmmConfigUpdate) -> Result<()> {
+        instructions::update_amm_config(ctx, update)
     }
 
     /// 3.为给定的代币对和初始价格创建交易池
@@ -110,13 +116,15 @@ pub mod my_clmm {
     ///           remove operation owner when the `param` is 1
     ///           update whitelist mint when the `param` is 2
     ///           remove whitelist mint when the `param` is 3
+    /// * `roles`- bitmask of `operation_role` given to `keys`, only used when the `param` is 0
     ///
     pub fn update_operation_account(
         ctx: Context<UpdateOperationAccount>,
         param: u8,
         keys: Vec<Pubkey>,
+        roles: u8,
     ) -> Result<()> {
-        instructions::update_operation_account(ctx, param, keys)
+        instructions::update_operation_account(ctx, param, keys, roles)
     }
 
     /// 7.Transfer reward owner
@@ -149,4 +157,69 @@ pub mod my_clmm {
     ) -> Result<()> {
         instructions::initialize_reward(ctx, param)
     }
+
+    /// 9.Update amm config status, a disabled config can not be used to create new pools,
+    /// pools already created on it keep working
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `status` - The vaule of status, bit0 set means creating pool is disabled,
+    ///              bit1 set means only creators with permission can create pools
+    ///
+    pub fn update_amm_config_status(ctx: Context<UpdateAmmConfigStatus>, status: u8) -> Result<()> {
+        instructions::update_amm_config_status(ctx, status)
+    }
+
+    /// 10.Grant a creator the permission to create pools on a permissioned amm config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn create_pool_creator_permission(ctx: Context<CreatePoolCreatorPermission>) -> Result<()> {
+        instructions::create_pool_creator_permission(ctx)
+    }
+
+    /// 11.Revoke the permission of a creator by closing the permission account
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn close_pool_creator_permission(ctx: Context<ClosePoolCreatorPermission>) -> Result<()> {
+        instructions::close_pool_creator_permission(ctx)
+    }
+
+    /// 12.Add a token-2022 mint to the on-chain whitelist, the extensions the mint has now
+    /// are recorded as reviewed
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn create_support_mint_associated(ctx: Context<CreateSupportMintAssociated>) -> Result<()> {
+        instructions::create_support_mint_associated(ctx)
+    }
+
+    /// 13.Remove a mint from the on-chain whitelist
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn close_support_mint_associated(ctx: Context<CloseSupportMintAssociated>) -> Result<()> {
+        instructions::close_support_mint_associated(ctx)
+    }
+
+    /// 14.Migrate the legacy zero copy operation account to the new operation account,
+    /// the legacy operation owners are given all roles
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_operation_account(ctx: Context<MigrateOperationAccount>) -> Result<()> {
+        instructions::migrate_operation_account(ctx)
+    }
 }
@@ -342,3 +342,267 @@ macro_rules! construct_bignum {
 construct_bignum! {
     pub struct U1024(16);
 }
+
+/// 有符号的 256 位整数,以补码的形式保存在 U256 中
+///
+/// 用于流动性变化量、手续费增长量相减以及 tick 累积值等需要有符号宽整数的计算。
+/// `wrapping_*` 按 2^256 取模回绕,`checked_*` 在结果超出 [I256::MIN, I256::MAX] 时返回 None
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct I256(pub U256);
+
+impl I256 {
+    /// -2^255
+    pub const MIN: I256 = I256(U256([0, 0, 0, 1 << 63]));
+    /// 2^255 - 1
+    pub const MAX: I256 = I256(U256([u64::MAX, u64::MAX, u64::MAX, i64::MAX as u64]));
+
+    #[inline]
+    pub const fn zero() -> Self {
+        I256(U256([0; 4]))
+    }
+
+    #[inline]
+    pub const fn one() -> Self {
+        I256(U256([1, 0, 0, 0]))
+    }
+
+    #[inline]
+    pub const fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// 最高位为 1 表示负数
+    #[inline]
+    pub const fn is_negative(&self) -> bool {
+        self.0.bit(255)
+    }
+
+    /// 用符号和绝对值构造,超出范围时返回 None
+    pub fn from_sign_and_abs(negative: bool, abs: U256) -> Option<Self> {
+        if negative {
+            if abs > Self::MIN.0 {
+                None
+            } else {
+                Some(I256(abs).wrapping_neg())
+            }
+        } else if abs > Self::MAX.0 {
+            None
+        } else {
+            Some(I256(abs))
+        }
+    }
+
+    /// 绝对值,I256::MIN 的绝对值 2^255 也能用 U256 表示
+    pub fn unsigned_abs(&self) -> U256 {
+        if self.is_negative() {
+            self.wrapping_neg().0
+        } else {
+            self.0
+        }
+    }
+
+    pub fn wrapping_neg(self) -> Self {
+        I256((!self.0).overflowing_add(U256::one()).0)
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        if self == Self::MIN {
+            None
+        } else {
+            Some(self.wrapping_neg())
+        }
+    }
+
+    pub fn wrapping_add(self, other: Self) -> Self {
+        I256(self.0.overflowing_add(other.0).0)
+    }
+
+    pub fn wrapping_sub(self, other: Self) -> Self {
+        I256(self.0.overflowing_sub(other.0).0)
+    }
+
+    pub fn wrapping_mul(self, other: Self) -> Self {
+        I256(self.0.overflowing_mul(other.0).0)
+    }
+
+    /// 两个同号的数相加后符号改变说明溢出
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let r = self.wrapping_add(other);
+        if self.is_negative() == other.is_negative() && r.is_negative() != self.is_negative() {
+            None
+        } else {
+            Some(r)
+        }
+    }
+
+    /// 两个异号的数相减后符号和被减数不同说明溢出
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let r = self.wrapping_sub(other);
+        if self.is_negative() != other.is_negative() && r.is_negative() != self.is_negative() {
+            None
+        } else {
+            Some(r)
+        }
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let abs = self.unsigned_abs().checked_mul(other.unsigned_abs())?;
+        Self::from_sign_and_abs(self.is_negative() != other.is_negative(), abs)
+    }
+}
+
+impl From<i128> for I256 {
+    fn from(value: i128) -> I256 {
+        // 符号扩展
+        let high = if value < 0 { u64::MAX } else { 0 };
+        I256(U256([value as u64, (value >> 64) as u64, high, high]))
+    }
+}
+
+impl core::convert::TryFrom<I256> for i128 {
+    type Error = &'static str;
+
+    fn try_from(value: I256) -> core::result::Result<i128, &'static str> {
+        if value < I256::from(i128::MIN) || value > I256::from(i128::MAX) {
+            return Err("integer overflow when casting to i128");
+        }
+        Ok(value.0.low_u128() as i128)
+    }
+}
+
+impl PartialOrd for I256 {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for I256 {
+    /// 翻转符号位后按无符号数比较
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.0 ^ Self::MIN.0).cmp(&(other.0 ^ Self::MIN.0))
+    }
+}
+
+impl core::fmt::Display for I256 {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.is_negative() {
+            write!(f, "-")?;
+        }
+        core::fmt::Display::fmt(&self.unsigned_abs(), f)
+    }
+}
+
+impl core::fmt::Debug for I256 {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn i(value: i128) -> I256 {
+        I256::from(value)
+    }
+
+    #[test]
+    fn checked_arithmetic_overflow() {
+        assert_eq!(I256::MAX.checked_add(I256::one()), None);
+        assert_eq!(I256::MIN.checked_add(i(-1)), None);
+        assert_eq!(
+            I256::MAX.checked_add(i(-1)),
+            Some(I256::MAX.wrapping_sub(I256::one()))
+        );
+        assert_eq!(I256::MIN.checked_add(I256::MAX), Some(i(-1)));
+
+        assert_eq!(I256::MIN.checked_sub(I256::one()), None);
+        assert_eq!(I256::MAX.checked_sub(i(-1)), None);
+        assert_eq!(I256::zero().checked_sub(I256::MIN), None);
+        assert_eq!(i(-1).checked_sub(I256::MIN), Some(I256::MAX));
+
+        assert_eq!(I256::MAX.checked_mul(i(2)), None);
+        assert_eq!(I256::MIN.checked_mul(i(-1)), None);
+        assert_eq!(I256::MIN.checked_mul(I256::one()), Some(I256::MIN));
+        assert_eq!(
+            I256::MAX.checked_mul(i(-1)),
+            Some(I256::MIN.wrapping_add(I256::one()))
+        );
+        // 2^127 * -2^128 = -2^255 刚好是 I256::MIN
+        let half = I256(U256::one() << 127);
+        assert_eq!(
+            half.checked_mul(I256(U256::one() << 128).wrapping_neg()),
+            Some(I256::MIN)
+        );
+        assert_eq!(half.checked_mul(I256(U256::one() << 128)), None);
+
+        assert_eq!(I256::MIN.checked_neg(), None);
+        assert_eq!(
+            I256::MAX.checked_neg(),
+            Some(I256::MIN.wrapping_add(I256::one()))
+        );
+        assert_eq!(I256::MIN.unsigned_abs(), U256::one() << 255);
+    }
+
+    #[test]
+    fn wrapping_arithmetic() {
+        assert_eq!(I256::MAX.wrapping_add(I256::one()), I256::MIN);
+        assert_eq!(I256::MIN.wrapping_sub(I256::one()), I256::MAX);
+        assert_eq!(I256::MIN.wrapping_neg(), I256::MIN);
+        assert_eq!(i(-3).wrapping_mul(i(7)), i(-21));
+    }
+
+    #[test]
+    fn i128_round_trip() {
+        for value in [0, 1, -1, 42, -42, i128::MAX, i128::MIN, i128::MIN + 1] {
+            assert_eq!(i128::try_from(I256::from(value)), Ok(value));
+            assert_eq!(I256::from(value).is_negative(), value < 0);
+        }
+        assert!(i128::try_from(I256::from(i128::MAX).wrapping_add(I256::one())).is_err());
+        assert!(i128::try_from(I256::from(i128::MIN).wrapping_sub(I256::one())).is_err());
+        assert!(i128::try_from(I256::MAX).is_err());
+        assert!(i128::try_from(I256::MIN).is_err());
+    }
+
+    #[test]
+    fn from_sign_and_abs() {
+        assert_eq!(I256::from_sign_and_abs(true, U256::from(5)), Some(i(-5)));
+        assert_eq!(I256::from_sign_and_abs(false, U256::from(5)), Some(i(5)));
+        assert_eq!(
+            I256::from_sign_and_abs(true, U256::one() << 255),
+            Some(I256::MIN)
+        );
+        assert_eq!(I256::from_sign_and_abs(false, U256::one() << 255), None);
+        assert_eq!(I256::from_sign_and_abs(true, U256::MAX), None);
+    }
+
+    #[test]
+    fn ordering_across_sign() {
+        let mut values = vec![
+            I256::MAX,
+            i(1),
+            I256::MIN,
+            i(-1),
+            I256::zero(),
+            i(i128::MIN),
+            i(i128::MAX),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                I256::MIN,
+                i(i128::MIN),
+                i(-1),
+                I256::zero(),
+                i(1),
+                i(i128::MAX),
+                I256::MAX
+            ]
+        );
+        assert!(i(-1) < I256::zero());
+        assert!(I256::MIN < I256::MAX);
+        assert_eq!(i(-12345).to_string(), "-12345");
+        assert_eq!(I256::MIN.to_string(), format!("-{}", U256::one() << 255));
+    }
+}
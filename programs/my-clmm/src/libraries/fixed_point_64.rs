@@ -1,5 +1,196 @@
 /// 处理 Q64.64 固定点数的库
 /// 用于 sqrt_price_math.rs 和 liquidity_amounts.rs
+use crate::libraries::big_num::U256;
+use anchor_lang::prelude::*;
+use std::fmt;
 
 pub const Q64: u128 = (u64::MAX as u128) + 1; // 2^64
 pub const RESOLUTION: u8 = 64;
+
+/// Q64.64 定点数,高 64 位是整数部分,低 64 位是小数部分
+///
+/// 和账户中保存的 `_x64` 字段(sqrt_price_x64、fee_growth_global_0_x64、reward_growth_global_x64 等)
+/// 的二进制表示完全相同,用 `from_bits`/`to_bits` 转换即可,不需要改变账户布局。
+/// 所有运算都是 checked 的,溢出或除以 0 时返回 None
+#[derive(
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    AnchorSerialize,
+    AnchorDeserialize,
+)]
+#[repr(transparent)]
+pub struct Q64x64(u128);
+
+impl Q64x64 {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(Q64);
+    pub const MAX: Self = Self(u128::MAX);
+
+    /// 直接使用 Q64.64 的原始表示
+    pub const fn from_bits(bits: u128) -> Self {
+        Self(bits)
+    }
+
+    /// Q64.64 的原始表示,可以直接保存到账户的 `_x64` 字段中
+    pub const fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    /// 整数转换成 Q64.64,u64 的整数一定不会溢出
+    pub const fn from_int(value: u64) -> Self {
+        Self((value as u128) << RESOLUTION)
+    }
+
+    /// `numerator / denominator` 向下取整到 Q64.64 的精度
+    pub fn from_ratio(numerator: u128, denominator: u128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let result = (U256::from(numerator) << RESOLUTION) / U256::from(denominator);
+        Self::from_u256(result)
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    /// 乘积向下取整到 Q64.64 的精度
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let result = (U256::from(self.0) * U256::from(rhs.0)) >> RESOLUTION;
+        Self::from_u256(result)
+    }
+
+    /// 商向下取整到 Q64.64 的精度
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        Self::from_ratio(self.0, rhs.0)
+    }
+
+    /// 整数部分,即向下取整
+    pub const fn floor_u64(self) -> u64 {
+        (self.0 >> RESOLUTION) as u64
+    }
+
+    /// 向上取整,结果超出 u64 时返回 None
+    pub fn ceil_u64(self) -> Option<u64> {
+        let floor = self.floor_u64();
+        if self.fractional_bits() == 0 {
+            Some(floor)
+        } else {
+            floor.checked_add(1)
+        }
+    }
+
+    /// 小数部分的原始表示
+    const fn fractional_bits(self) -> u64 {
+        self.0 as u64
+    }
+
+    fn from_u256(value: U256) -> Option<Self> {
+        if value > U256::from(u128::MAX) {
+            None
+        } else {
+            Some(Self(value.as_u128()))
+        }
+    }
+}
+
+impl From<u64> for Q64x64 {
+    fn from(value: u64) -> Self {
+        Self::from_int(value)
+    }
+}
+
+/// 打印精确的十进制值,例如 `Q64x64::from_ratio(1, 4)` 打印为 `0.25`
+///
+/// 指定精度时(如 `{:.6}`)小数部分截断到该位数,不做四舍五入
+impl fmt::Display for Q64x64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // frac / 2^64 = frac * 5^64 / 10^64,所以小数部分最多有 64 位十进制数字
+        let digits = (U256::from(self.fractional_bits()) * U256::from(5u8).pow(U256::from(64u8)))
+            .to_string();
+        let fraction = format!("{:0>64}", digits);
+        let fraction = match f.precision() {
+            Some(precision) => format!("{:0<width$.width$}", fraction, width = precision),
+            None => fraction.trim_end_matches('0').to_string(),
+        };
+        if fraction.is_empty() {
+            write!(f, "{}", self.floor_u64())
+        } else {
+            write!(f, "{}.{}", self.floor_u64(), fraction)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_ratio_and_rounding() {
+        assert_eq!(Q64x64::from_ratio(1, 0), None);
+        assert_eq!(Q64x64::from_ratio(1, 1), Some(Q64x64::ONE));
+        assert_eq!(
+            Q64x64::from_ratio(u128::from(u64::MAX), 1),
+            Some(Q64x64::from_int(u64::MAX))
+        );
+        assert_eq!(Q64x64::from_ratio(u128::from(u64::MAX) + 1, 1), None);
+
+        let third = Q64x64::from_ratio(10, 3).unwrap();
+        assert_eq!(third.floor_u64(), 3);
+        assert_eq!(third.ceil_u64(), Some(4));
+        assert_eq!(Q64x64::from_int(7).ceil_u64(), Some(7));
+        assert_eq!(Q64x64::MAX.floor_u64(), u64::MAX);
+        assert_eq!(Q64x64::MAX.ceil_u64(), None);
+    }
+
+    #[test]
+    fn checked_operators() {
+        let half = Q64x64::from_ratio(1, 2).unwrap();
+        assert_eq!(half.checked_add(half), Some(Q64x64::ONE));
+        assert_eq!(half.checked_sub(Q64x64::ONE), None);
+        assert_eq!(Q64x64::MAX.checked_add(Q64x64::from_bits(1)), None);
+
+        assert_eq!(
+            Q64x64::from_int(6).checked_mul(half),
+            Some(Q64x64::from_int(3))
+        );
+        assert_eq!(Q64x64::MAX.checked_mul(Q64x64::ONE), Some(Q64x64::MAX));
+        assert_eq!(Q64x64::MAX.checked_mul(Q64x64::from_int(2)), None);
+        // 乘积的精度低于 2^-64 时向下取整
+        assert_eq!(Q64x64::from_bits(1).checked_mul(half), Some(Q64x64::ZERO));
+
+        assert_eq!(
+            Q64x64::from_int(3).checked_div(half),
+            Some(Q64x64::from_int(6))
+        );
+        assert_eq!(Q64x64::ONE.checked_div(Q64x64::ZERO), None);
+        assert_eq!(Q64x64::MAX.checked_div(half), None);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Q64x64::ZERO.to_string(), "0");
+        assert_eq!(Q64x64::from_int(42).to_string(), "42");
+        assert_eq!(Q64x64::from_ratio(5, 4).unwrap().to_string(), "1.25");
+        assert_eq!(
+            Q64x64::from_bits(1).to_string(),
+            "0.0000000000000000000542101086242752217003726400434970855712890625"
+        );
+        assert_eq!(
+            format!("{:.6}", Q64x64::from_ratio(1, 3).unwrap()),
+            "0.333333"
+        );
+        assert_eq!(format!("{:.3}", Q64x64::from_int(2)), "2.000");
+    }
+}
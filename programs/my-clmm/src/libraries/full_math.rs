@@ -3,7 +3,7 @@
 //! and supports U128 operations.
 //!
 
-use crate::libraries::big_num::{U128, U256, U512};
+use crate::libraries::big_num::{I256, U128, U256, U512};
 
 /// Trait for calculating `val * num / denom` with different rounding modes and overflow
 /// protection.
@@ -22,7 +22,7 @@ use crate::libraries::big_num::{U128, U256, U512};
 /// 100u64 * 200u64 = 20,000   // 中间结果能放进 u64
 /// 20,000 / 50 = 400          // 最终结果也能放进 u64
 /// 3.返回none的情况
-/// 只有当最终结果超出类型范围时才返回 None
+/// 只有当最终结果超出类型范围或者分母为 0 时才返回 None
 /// 例如: (2^63 * 4) / 1    // 最终结果 2^65 超出 u64 范围
 ///
 /// This specifically means that e.g. the `u64` implementation must, depending on the arguments, be
@@ -38,25 +38,19 @@ pub trait MulDiv<RHS = Self> {
     /// ## Example
     ///
     /// ```rust
-    /// use libraries::full_math::MulDiv;
+    /// use my_clmm::libraries::full_math::MulDiv;
     ///
     /// # fn main() {
-    /// let x = 3i8.mul_div_floor(4, 2);
+    /// let x = 3u64.mul_div_floor(4, 2);
     /// assert_eq!(x, Some(6));
     ///
-    /// let x = 5i8.mul_div_floor(2, 3);
+    /// let x = 5u64.mul_div_floor(2, 3);
     /// assert_eq!(x, Some(3));
     ///
-    /// let x = (-5i8).mul_div_floor(2, 3);
-    /// assert_eq!(x, Some(-4));
-    ///
-    /// let x = 3i8.mul_div_floor(3, 2);
-    /// assert_eq!(x, Some(4));
-    ///
-    /// let x = (-3i8).mul_div_floor(3, 2);
-    /// assert_eq!(x, Some(-5));
+    /// let x = u64::MAX.mul_div_floor(4, 3);
+    /// assert_eq!(x, None);
     ///
-    /// let x = 127i8.mul_div_floor(4, 3);
+    /// let x = 5u64.mul_div_floor(2, 0);
     /// assert_eq!(x, None);
     /// # }
     /// ```
@@ -68,31 +62,26 @@ pub trait MulDiv<RHS = Self> {
     /// ## Example
     ///
     /// ```rust
-    /// use libraries::full_math::MulDiv;
+    /// use my_clmm::libraries::full_math::MulDiv;
     ///
     /// # fn main() {
-    /// let x = 3i8.mul_div_ceil(4, 2);
+    /// let x = 3u64.mul_div_ceil(4, 2);
     /// assert_eq!(x, Some(6));
     ///
-    /// let x = 5i8.mul_div_ceil(2, 3);
+    /// let x = 5u64.mul_div_ceil(2, 3);
     /// assert_eq!(x, Some(4));
     ///
-    /// let x = (-5i8).mul_div_ceil(2, 3);
-    /// assert_eq!(x, Some(-3));
-    ///
-    /// let x = 3i8.mul_div_ceil(3, 2);
-    /// assert_eq!(x, Some(5));
-    ///
-    /// let x = (-3i8).mul_div_ceil(3, 2);
-    /// assert_eq!(x, Some(-4));
+    /// let x = u64::MAX.mul_div_ceil(4, 3);
+    /// assert_eq!(x, None);
     ///
-    /// let x = (127i8).mul_div_ceil(4, 3);
+    /// let x = 5u64.mul_div_ceil(2, 0);
     /// assert_eq!(x, None);
     /// # }
     /// ```
     fn mul_div_ceil(self, num: RHS, denom: RHS) -> Option<Self::Output>;
 
     /// Return u64 not out of bounds
+    /// 值能放进 u64 时原样返回,否则返回 0,不同位宽的实现行为一致
     fn to_underflow_u64(self) -> u64;
 }
 
@@ -145,7 +134,9 @@ impl MulDiv for u64 {
     type Output = u64;
     ///(self * num) / denom，并且向下取整
     fn mul_div_floor(self, num: Self, denom: Self) -> Option<Self::Output> {
-        assert_ne!(denom, 0);
+        if denom == 0 {
+            return None;
+        }
         // 1. 先将所有数转换为 U128 以防止中间计算溢出
         // 2. 执行乘法和除法运算
         let r = (U128::from(self) * U128::from(num)) / U128::from(denom);
@@ -159,7 +150,9 @@ impl MulDiv for u64 {
 
     ///(self * num) / denom，并且向上取整
     fn mul_div_ceil(self, num: Self, denom: Self) -> Option<Self::Output> {
-        assert_ne!(denom, 0);
+        if denom == 0 {
+            return None;
+        }
         let r = (U128::from(self) * U128::from(num) + U128::from(denom - 1)) / U128::from(denom);
         if r > U128::from(u64::MAX) {
             None
@@ -179,7 +172,9 @@ impl MulDiv for U128 {
     type Output = U128;
 
     fn mul_div_floor(self, num: Self, denom: Self) -> Option<Self::Output> {
-        assert_ne!(denom, U128::default());
+        if denom.is_zero() {
+            return None;
+        }
         let r = ((self.as_u256()) * (num.as_u256())) / (denom.as_u256());
         if r > U128::MAX.as_u256() {
             None
@@ -189,7 +184,9 @@ impl MulDiv for U128 {
     }
 
     fn mul_div_ceil(self, num: Self, denom: Self) -> Option<Self::Output> {
-        assert_ne!(denom, U128::default());
+        if denom.is_zero() {
+            return None;
+        }
         let r = (self.as_u256() * num.as_u256() + (denom - 1).as_u256()) / denom.as_u256();
         if r > U128::MAX.as_u256() {
             None
@@ -199,7 +196,7 @@ impl MulDiv for U128 {
     }
 
     fn to_underflow_u64(self) -> u64 {
-        if self < U128::from(u64::MAX) {
+        if self <= U128::from(u64::MAX) {
             self.as_u64()
         } else {
             0
@@ -211,7 +208,9 @@ impl MulDiv for U256 {
     type Output = U256;
 
     fn mul_div_floor(self, num: Self, denom: Self) -> Option<Self::Output> {
-        assert_ne!(denom, U256::default());
+        if denom.is_zero() {
+            return None;
+        }
         let r = (self.as_u512() * num.as_u512()) / denom.as_u512();
         if r > U256::MAX.as_u512() {
             None
@@ -221,7 +220,9 @@ impl MulDiv for U256 {
     }
 
     fn mul_div_ceil(self, num: Self, denom: Self) -> Option<Self::Output> {
-        assert_ne!(denom, U256::default());
+        if denom.is_zero() {
+            return None;
+        }
         let r = (self.as_u512() * num.as_u512() + (denom - 1).as_u512()) / denom.as_u512();
         if r > U256::MAX.as_u512() {
             None
@@ -231,10 +232,114 @@ impl MulDiv for U256 {
     }
 
     fn to_underflow_u64(self) -> u64 {
-        if self < U256::from(u64::MAX) {
+        if self <= U256::from(u64::MAX) {
             self.as_u64()
         } else {
             0
         }
     }
 }
+
+/// 有符号的实现,floor 向负无穷取整,ceil 向正无穷取整
+impl MulDiv for I256 {
+    type Output = I256;
+
+    fn mul_div_floor(self, num: Self, denom: Self) -> Option<Self::Output> {
+        signed_mul_div(self, num, denom, false)
+    }
+
+    fn mul_div_ceil(self, num: Self, denom: Self) -> Option<Self::Output> {
+        signed_mul_div(self, num, denom, true)
+    }
+
+    /// 负数和超出 u64 范围的数都返回 0
+    fn to_underflow_u64(self) -> u64 {
+        if self.is_negative() {
+            0
+        } else {
+            self.0.to_underflow_u64()
+        }
+    }
+}
+
+/// 先用绝对值计算 |val * num| / |denom|,再根据符号和余数决定取整方向
+fn signed_mul_div(val: I256, num: I256, denom: I256, round_up: bool) -> Option<I256> {
+    if denom.is_zero() {
+        return None;
+    }
+    let negative = (val.is_negative() ^ num.is_negative()) ^ denom.is_negative();
+    let (quotient, remainder) = (val.unsigned_abs().as_u512() * num.unsigned_abs().as_u512())
+        .div_mod(denom.unsigned_abs().as_u512());
+    // 负数的 floor 和正数的 ceil 都需要让绝对值加 1
+    let quotient = if !remainder.is_zero() && negative != round_up {
+        quotient + U512::one()
+    } else {
+        quotient
+    };
+    if quotient > U256::MAX.as_u512() {
+        return None;
+    }
+    I256::from_sign_and_abs(negative && !quotient.is_zero(), quotient.as_u256())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn i(value: i128) -> I256 {
+        I256::from(value)
+    }
+
+    #[test]
+    fn signed_mul_div_rounding() {
+        // 7 * 1 / 2 = 3.5
+        assert_eq!(i(7).mul_div_floor(i(1), i(2)), Some(i(3)));
+        assert_eq!(i(7).mul_div_ceil(i(1), i(2)), Some(i(4)));
+        // 结果为 -3.5,floor 向负无穷取 -4,ceil 向正无穷取 -3,负号在哪一个参数上结果都一样
+        for (val, num, denom) in [(-7, 1, 2), (7, -1, 2), (7, 1, -2), (-7, -1, -2)] {
+            assert_eq!(i(val).mul_div_floor(i(num), i(denom)), Some(i(-4)));
+            assert_eq!(i(val).mul_div_ceil(i(num), i(denom)), Some(i(-3)));
+        }
+        // 两个负号相互抵消
+        assert_eq!(i(-7).mul_div_floor(i(1), i(-2)), Some(i(3)));
+        assert_eq!(i(-7).mul_div_ceil(i(1), i(-2)), Some(i(4)));
+        // 整除时不需要取整
+        assert_eq!(i(-8).mul_div_floor(i(1), i(2)), Some(i(-4)));
+        assert_eq!(i(-8).mul_div_ceil(i(1), i(2)), Some(i(-4)));
+        // 绝对值小于 1 的负数 floor 为 -1,ceil 为 0 而不是 -0
+        assert_eq!(i(-1).mul_div_floor(i(1), i(3)), Some(i(-1)));
+        assert_eq!(i(-1).mul_div_ceil(i(1), i(3)), Some(I256::zero()));
+        assert!(!i(-1).mul_div_ceil(i(1), i(3)).unwrap().is_negative());
+    }
+
+    #[test]
+    fn signed_mul_div_overflow() {
+        assert_eq!(i(1).mul_div_floor(i(1), I256::zero()), None);
+        assert_eq!(i(-1).mul_div_ceil(i(1), I256::zero()), None);
+        // 中间结果超过 256 位也能得到正确的结果
+        assert_eq!(
+            I256::MAX.mul_div_floor(I256::MAX, I256::MAX),
+            Some(I256::MAX)
+        );
+        assert_eq!(I256::MIN.mul_div_floor(i(3), i(3)), Some(I256::MIN));
+        assert_eq!(I256::MIN.mul_div_floor(i(-1), i(1)), None);
+        assert_eq!(I256::MAX.mul_div_floor(i(2), i(1)), None);
+        assert_eq!(
+            I256::MIN.mul_div_ceil(I256::MAX, I256::MAX),
+            Some(I256::MIN)
+        );
+        assert_eq!(I256::MIN.mul_div_floor(i(4), i(3)), None);
+        // (2^255 - 1) * -1 / 1 = -2^255 + 1 不会溢出
+        assert_eq!(
+            I256::MAX.mul_div_floor(i(-1), i(1)),
+            Some(I256::MIN.wrapping_add(i(1)))
+        );
+    }
+
+    #[test]
+    fn signed_to_underflow_u64() {
+        assert_eq!(i(-1).to_underflow_u64(), 0);
+        assert_eq!(i(42).to_underflow_u64(), 42);
+        assert_eq!(i(u64::MAX as i128 + 1).to_underflow_u64(), 0);
+    }
+}
@@ -0,0 +1,34 @@
+/// 流动性的加减
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// `x` 加上有符号的 `y`,结果不能小于 0 或者溢出
+pub fn add_delta(x: u128, y: i128) -> Result<u128> {
+    if y < 0 {
+        x.checked_sub(y.unsigned_abs())
+            .ok_or_else(|| error!(ErrorCode::LiquiditySubValueErr))
+    } else {
+        x.checked_add(y as u128)
+            .ok_or_else(|| error!(ErrorCode::LiquidityAddValueErr))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn liquidity_delta() {
+        assert_eq!(add_delta(1, 0).unwrap(), 1);
+        assert_eq!(add_delta(1, -1).unwrap(), 0);
+        assert_eq!(add_delta(1, 1).unwrap(), 2);
+        assert_eq!(
+            add_delta(1, i128::MIN + 1).unwrap_err(),
+            ErrorCode::LiquiditySubValueErr.into()
+        );
+        assert_eq!(
+            add_delta(u128::MAX, 1).unwrap_err(),
+            ErrorCode::LiquidityAddValueErr.into()
+        );
+    }
+}
@@ -9,3 +9,18 @@ pub use full_math::*;
 
 pub mod fixed_point_64;
 pub use fixed_point_64::*;
+
+pub mod price_math;
+pub use price_math::*;
+
+pub mod tick_utils;
+pub use tick_utils::*;
+
+pub mod sqrt_price_math;
+pub use sqrt_price_math::*;
+
+pub mod swap_math;
+pub use swap_math::*;
+
+pub mod liquidity_math;
+pub use liquidity_math::*;
@@ -0,0 +1,185 @@
+/// 人类可读的价格与 sqrt_price_x64、tick 之间的转换
+///
+/// 这里的价格指 1 个 token_0 值多少个 token_1,并且已经按照两种代币的 decimals 换算过,
+/// 例如 ETH(decimals 9)/USDC(decimals 6) 池子中的 1850.25 表示 1 ETH = 1850.25 USDC。
+/// 链上保存的是最小单位之间的比例,所以转换时需要乘以 10^(decimals_1 - decimals_0)
+use crate::error::ErrorCode;
+use crate::libraries::{fixed_point_64, tick_math, tick_utils};
+use anchor_lang::prelude::*;
+
+/// 价格转换成 sqrt_price_x64,结果必须落在 [MIN_SQRT_PRICE_X64, MAX_SQRT_PRICE_X64] 内
+///
+/// f64 只有 53 位有效位,结果的精度也只有这么多,适合把人输入的价格转换成初始价格
+pub fn price_to_sqrt_price_x64(price: f64, decimals_0: u8, decimals_1: u8) -> Result<u128> {
+    require!(price.is_finite() && price > 0.0, ErrorCode::SqrtPriceX64);
+    // 最小单位之间的价格
+    let raw_price = price * decimals_multiplier(decimals_1, decimals_0);
+    let sqrt_price_x64 = raw_price.sqrt() * fixed_point_64::Q64 as f64;
+    require!(
+        sqrt_price_x64 >= tick_math::MIN_SQRT_PRICE_X64 as f64
+            && sqrt_price_x64 <= tick_math::MAX_SQRT_PRICE_X64 as f64,
+        ErrorCode::SqrtPriceX64
+    );
+    Ok(sqrt_price_x64 as u128)
+}
+
+/// sqrt_price_x64 转换成价格
+pub fn sqrt_price_x64_to_price(sqrt_price_x64: u128, decimals_0: u8, decimals_1: u8) -> f64 {
+    let sqrt_price = sqrt_price_x64 as f64 / fixed_point_64::Q64 as f64;
+    sqrt_price * sqrt_price * decimals_multiplier(decimals_0, decimals_1)
+}
+
+/// 价格转换成 tick_spacing 的整数倍的 tick
+///
+/// `round_up` 为 false 时返回价格不高于 `price` 的最大的有效 tick,
+/// 为 true 时返回价格不低于 `price` 的最小的有效 tick
+pub fn price_to_tick(
+    price: f64,
+    decimals_0: u8,
+    decimals_1: u8,
+    tick_spacing: u16,
+    round_up: bool,
+) -> Result<i32> {
+    require!(tick_spacing > 0, ErrorCode::TickAndSpacingNotMatch);
+    let sqrt_price_x64 = price_to_sqrt_price_x64(price, decimals_0, decimals_1)?;
+    // get_tick_at_sqrt_price 的定义域不包含 MAX_SQRT_PRICE_X64
+    let tick = if sqrt_price_x64 >= tick_math::MAX_SQRT_PRICE_X64 {
+        tick_math::MAX_TICK
+    } else {
+        tick_math::get_tick_at_sqrt_price(sqrt_price_x64)?
+    };
+    let spacing = i32::from(tick_spacing);
+    let mut aligned_tick = tick.div_euclid(spacing) * spacing;
+    // 低于 MIN_TICK 的 tick 的价格一定低于 price
+    if round_up
+        && (aligned_tick < tick_math::MIN_TICK
+            || tick_math::get_sqrt_price_at_tick(aligned_tick)? < sqrt_price_x64)
+    {
+        aligned_tick += spacing;
+    }
+    tick_utils::check_tick_boundary(aligned_tick, tick_spacing)?;
+    Ok(aligned_tick)
+}
+
+/// tick 对应的价格
+pub fn tick_to_price(tick: i32, decimals_0: u8, decimals_1: u8) -> Result<f64> {
+    let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(tick)?;
+    Ok(sqrt_price_x64_to_price(
+        sqrt_price_x64,
+        decimals_0,
+        decimals_1,
+    ))
+}
+
+/// 10^(decimals_a - decimals_b)
+fn decimals_multiplier(decimals_a: u8, decimals_b: u8) -> f64 {
+    10f64.powi(i32::from(decimals_a) - i32::from(decimals_b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            ((actual - expected) / expected).abs() < 1e-12,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn price_round_trip() {
+        for (decimals_0, decimals_1) in [(6, 6), (9, 6), (6, 9), (0, 12)] {
+            for price in [1e-6, 0.5, 1.0, 1850.25, 123_456.789] {
+                let sqrt_price_x64 =
+                    price_to_sqrt_price_x64(price, decimals_0, decimals_1).unwrap();
+                assert_close(
+                    sqrt_price_x64_to_price(sqrt_price_x64, decimals_0, decimals_1),
+                    price,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decimals() {
+        // ETH(decimals 9)/USDC(decimals 6): 1 ETH = 1850.25 USDC,
+        // 最小单位之间的价格是 1850.25 * 10^6 / 10^9 = 1.85025
+        let sqrt_price_x64 = price_to_sqrt_price_x64(1850.25, 9, 6).unwrap();
+        assert_close(
+            sqrt_price_x64 as f64 / fixed_point_64::Q64 as f64,
+            1.85025f64.sqrt(),
+        );
+        assert_close(sqrt_price_x64_to_price(sqrt_price_x64, 9, 6), 1850.25);
+        // 同样的 sqrt_price_x64 在 decimals 相反的池子中价格相差 10^6 倍
+        assert_close(sqrt_price_x64_to_price(sqrt_price_x64, 6, 9), 1.85025e-3);
+
+        // 1.0001^(-6932) ≈ 0.5,换算 decimals 后是 0.5 * 10^3
+        let price = tick_to_price(-6932, 9, 6).unwrap();
+        assert!((price - 500.0).abs() < 0.1);
+        assert_eq!(
+            price_to_tick(price * 1.000_01, 9, 6, 1, false).unwrap(),
+            -6932
+        );
+    }
+
+    #[test]
+    fn price_to_tick_rounding() {
+        // 1.0001^t = 0.5 时 t ≈ -6931.5
+        assert_eq!(price_to_tick(0.5, 6, 6, 1, false).unwrap(), -6932);
+        assert_eq!(price_to_tick(0.5, 6, 6, 1, true).unwrap(), -6931);
+        // 按 tick_spacing 对齐时负数向负无穷取整
+        assert_eq!(price_to_tick(0.5, 6, 6, 60, false).unwrap(), -6960);
+        assert_eq!(price_to_tick(0.5, 6, 6, 60, true).unwrap(), -6900);
+        assert_eq!(price_to_tick(2.0, 6, 6, 60, false).unwrap(), 6900);
+        assert_eq!(price_to_tick(2.0, 6, 6, 60, true).unwrap(), 6960);
+        for (tick_spacing, round_up) in [(1, false), (1, true), (10, false), (10, true), (60, true)]
+        {
+            for price in [0.5, 0.999, 1.0001, 2.0, 1234.5] {
+                let tick = price_to_tick(price, 6, 6, tick_spacing, round_up).unwrap();
+                assert_eq!(tick % i32::from(tick_spacing), 0);
+                let tick_price = tick_to_price(tick, 6, 6).unwrap();
+                if round_up {
+                    assert!(tick_price >= price * (1.0 - 1e-12));
+                    let prev = tick_to_price(tick - i32::from(tick_spacing), 6, 6).unwrap();
+                    assert!(prev < price);
+                } else {
+                    assert!(tick_price <= price * (1.0 + 1e-12));
+                    let next = tick_to_price(tick + i32::from(tick_spacing), 6, 6).unwrap();
+                    assert!(next > price);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_price() {
+        for price in [0.0, -0.0, -1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(
+                price_to_sqrt_price_x64(price, 6, 6).unwrap_err(),
+                ErrorCode::SqrtPriceX64.into()
+            );
+            assert_eq!(
+                price_to_tick(price, 6, 6, 1, false).unwrap_err(),
+                ErrorCode::SqrtPriceX64.into()
+            );
+        }
+        // 超出 [MIN_SQRT_PRICE_X64, MAX_SQRT_PRICE_X64]
+        for price in [1e-40, 1e40] {
+            assert_eq!(
+                price_to_sqrt_price_x64(price, 6, 6).unwrap_err(),
+                ErrorCode::SqrtPriceX64.into()
+            );
+        }
+        // decimals 换算后才超出范围
+        assert!(price_to_sqrt_price_x64(1e-15, 6, 6).is_ok());
+        assert!(price_to_sqrt_price_x64(1e-15, 18, 0).is_err());
+        assert_eq!(
+            price_to_tick(1.0, 6, 6, 0, false).unwrap_err(),
+            ErrorCode::TickAndSpacingNotMatch.into()
+        );
+        assert!(tick_to_price(tick_math::MAX_TICK + 1, 6, 6).is_err());
+    }
+}
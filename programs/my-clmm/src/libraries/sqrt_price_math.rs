@@ -0,0 +1,291 @@
+/// 根据流动性和代币数量计算价格的变化,以及两个价格之间需要的代币数量
+///
+/// 所有的取整方向都偏向池子:用户支付的数量向上取整,用户得到的数量向下取整
+use crate::error::ErrorCode;
+use crate::libraries::{fixed_point_64, full_math::MulDiv, U256};
+use anchor_lang::prelude::*;
+
+/// 向上取整的除法,`denominator` 不能为 0
+fn div_rounding_up(numerator: U256, denominator: U256) -> U256 {
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    if remainder.is_zero() {
+        quotient
+    } else {
+        quotient + U256::one()
+    }
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    require_gte!(U256::from(u128::MAX), value, ErrorCode::SqrtPriceX64);
+    Ok(value.as_u128())
+}
+
+fn to_u64(value: U256) -> Result<u64> {
+    require_gte!(U256::from(u64::MAX), value, ErrorCode::MaxTokenOverflow);
+    Ok(value.as_u64())
+}
+
+/// 增加或减少 `amount` 个 token_0 后的价格,向上取整
+///
+/// 公式为 `liquidity * sqrt_price / (liquidity ± amount * sqrt_price)`
+pub fn get_next_sqrt_price_from_amount_0_rounding_up(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price_x64);
+    }
+    let numerator_1 = U256::from(liquidity) << fixed_point_64::RESOLUTION;
+    let product = U256::from(amount) * U256::from(sqrt_price_x64);
+    if add {
+        let denominator = numerator_1
+            .checked_add(product)
+            .ok_or(ErrorCode::CalculateOverflow)?;
+        let result = numerator_1
+            .mul_div_ceil(U256::from(sqrt_price_x64), denominator)
+            .ok_or(ErrorCode::CalculateOverflow)?;
+        to_u128(result)
+    } else {
+        // 取出的 token_0 必须少于池子中的数量
+        require_gt!(numerator_1, product, ErrorCode::LiquidityInsufficient);
+        let result = numerator_1
+            .mul_div_ceil(U256::from(sqrt_price_x64), numerator_1 - product)
+            .ok_or(ErrorCode::CalculateOverflow)?;
+        to_u128(result)
+    }
+}
+
+/// 增加或减少 `amount` 个 token_1 后的价格,向下取整
+///
+/// 公式为 `sqrt_price ± amount / liquidity`
+pub fn get_next_sqrt_price_from_amount_1_rounding_down(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Result<u128> {
+    let numerator = U256::from(amount) << fixed_point_64::RESOLUTION;
+    if add {
+        let quotient = to_u128(numerator / U256::from(liquidity))?;
+        sqrt_price_x64
+            .checked_add(quotient)
+            .ok_or_else(|| error!(ErrorCode::SqrtPriceX64))
+    } else {
+        let quotient = to_u128(div_rounding_up(numerator, U256::from(liquidity)))?;
+        // 取出的 token_1 必须少于池子中的数量
+        require_gt!(sqrt_price_x64, quotient, ErrorCode::LiquidityInsufficient);
+        Ok(sqrt_price_x64 - quotient)
+    }
+}
+
+/// 输入 `amount_in` 个代币后的价格,zero_for_one 时输入的是 token_0
+pub fn get_next_sqrt_price_from_input(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount_in: u64,
+    zero_for_one: bool,
+) -> Result<u128> {
+    require!(sqrt_price_x64 > 0, ErrorCode::SqrtPriceX64);
+    require!(liquidity > 0, ErrorCode::LiquidityInsufficient);
+    if zero_for_one {
+        get_next_sqrt_price_from_amount_0_rounding_up(sqrt_price_x64, liquidity, amount_in, true)
+    } else {
+        get_next_sqrt_price_from_amount_1_rounding_down(sqrt_price_x64, liquidity, amount_in, true)
+    }
+}
+
+/// 输出 `amount_out` 个代币后的价格,zero_for_one 时输出的是 token_1
+pub fn get_next_sqrt_price_from_output(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount_out: u64,
+    zero_for_one: bool,
+) -> Result<u128> {
+    require!(sqrt_price_x64 > 0, ErrorCode::SqrtPriceX64);
+    require!(liquidity > 0, ErrorCode::LiquidityInsufficient);
+    if zero_for_one {
+        get_next_sqrt_price_from_amount_1_rounding_down(
+            sqrt_price_x64,
+            liquidity,
+            amount_out,
+            false,
+        )
+    } else {
+        get_next_sqrt_price_from_amount_0_rounding_up(sqrt_price_x64, liquidity, amount_out, false)
+    }
+}
+
+/// 两个价格之间 `liquidity` 对应的 token_0 数量
+///
+/// 公式为 `liquidity * (sqrt_price_b - sqrt_price_a) / (sqrt_price_a * sqrt_price_b)`
+pub fn get_delta_amount_0_unsigned(
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u64> {
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    }
+    require!(sqrt_ratio_a_x64 > 0, ErrorCode::SqrtPriceX64);
+    let numerator_1 = U256::from(liquidity) << fixed_point_64::RESOLUTION;
+    let numerator_2 = U256::from(sqrt_ratio_b_x64 - sqrt_ratio_a_x64);
+    let result = if round_up {
+        div_rounding_up(
+            numerator_1
+                .mul_div_ceil(numerator_2, U256::from(sqrt_ratio_b_x64))
+                .ok_or(ErrorCode::CalculateOverflow)?,
+            U256::from(sqrt_ratio_a_x64),
+        )
+    } else {
+        numerator_1
+            .mul_div_floor(numerator_2, U256::from(sqrt_ratio_b_x64))
+            .ok_or(ErrorCode::CalculateOverflow)?
+            / U256::from(sqrt_ratio_a_x64)
+    };
+    to_u64(result)
+}
+
+/// 两个价格之间 `liquidity` 对应的 token_1 数量
+///
+/// 公式为 `liquidity * (sqrt_price_b - sqrt_price_a)`
+pub fn get_delta_amount_1_unsigned(
+    mut sqrt_ratio_a_x64: u128,
+    mut sqrt_ratio_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u64> {
+    if sqrt_ratio_a_x64 > sqrt_ratio_b_x64 {
+        std::mem::swap(&mut sqrt_ratio_a_x64, &mut sqrt_ratio_b_x64);
+    }
+    let numerator = U256::from(liquidity);
+    let delta = U256::from(sqrt_ratio_b_x64 - sqrt_ratio_a_x64);
+    let denominator = U256::from(fixed_point_64::Q64);
+    let result = if round_up {
+        numerator.mul_div_ceil(delta, denominator)
+    } else {
+        numerator.mul_div_floor(delta, denominator)
+    }
+    .ok_or(ErrorCode::CalculateOverflow)?;
+    to_u64(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::libraries::tick_math;
+
+    const Q64: u128 = fixed_point_64::Q64;
+
+    #[test]
+    fn next_sqrt_price_from_amount_0() {
+        let liquidity = 1_000_000_000_000_000_000;
+        // 结果向上取整,价格的变化偏向池子
+        assert_eq!(
+            get_next_sqrt_price_from_amount_0_rounding_up(Q64, liquidity, 1_000_000, true).unwrap(),
+            18446744073691104872
+        );
+        assert_eq!(
+            get_next_sqrt_price_from_amount_0_rounding_up(Q64, liquidity, 1_000_000, false)
+                .unwrap(),
+            18446744073727998361
+        );
+        assert_eq!(
+            get_next_sqrt_price_from_amount_0_rounding_up(Q64, liquidity, 0, false).unwrap(),
+            Q64
+        );
+
+        // 取出的 token_0 不能达到池子中的数量
+        assert_eq!(
+            get_next_sqrt_price_from_amount_0_rounding_up(Q64, 1000, 1000, false).unwrap_err(),
+            ErrorCode::LiquidityInsufficient.into()
+        );
+        // 价格超出 u128
+        assert_eq!(
+            get_next_sqrt_price_from_amount_0_rounding_up((1 << 103) - 1, 1 << 40, 2, false)
+                .unwrap_err(),
+            ErrorCode::SqrtPriceX64.into()
+        );
+    }
+
+    #[test]
+    fn next_sqrt_price_from_amount_1() {
+        // 2^64 / 3 不能整除: 增加时向下取整,减少时向上取整
+        assert_eq!(
+            get_next_sqrt_price_from_amount_1_rounding_down(Q64, 3, 1, true).unwrap(),
+            24595658764946068821
+        );
+        assert_eq!(
+            get_next_sqrt_price_from_amount_1_rounding_down(Q64, 3, 1, false).unwrap(),
+            12297829382473034410
+        );
+
+        assert_eq!(
+            get_next_sqrt_price_from_amount_1_rounding_down(Q64, 1, 1, false).unwrap_err(),
+            ErrorCode::LiquidityInsufficient.into()
+        );
+        assert_eq!(
+            get_next_sqrt_price_from_amount_1_rounding_down(u128::MAX - Q64 + 1, 1, 1, true)
+                .unwrap_err(),
+            ErrorCode::SqrtPriceX64.into()
+        );
+    }
+
+    #[test]
+    fn next_sqrt_price_from_input_and_output() {
+        let liquidity = 1_000_000_000_000_000_000;
+        // 输入 token_0 价格下降,输入 token_1 价格上升
+        assert!(get_next_sqrt_price_from_input(Q64, liquidity, 1_000_000, true).unwrap() < Q64);
+        assert!(get_next_sqrt_price_from_input(Q64, liquidity, 1_000_000, false).unwrap() > Q64);
+        // 输出 token_1 价格下降,输出 token_0 价格上升
+        assert!(get_next_sqrt_price_from_output(Q64, liquidity, 1_000_000, true).unwrap() < Q64);
+        assert!(get_next_sqrt_price_from_output(Q64, liquidity, 1_000_000, false).unwrap() > Q64);
+
+        assert_eq!(
+            get_next_sqrt_price_from_input(0, liquidity, 1, true).unwrap_err(),
+            ErrorCode::SqrtPriceX64.into()
+        );
+        assert_eq!(
+            get_next_sqrt_price_from_output(Q64, 0, 1, true).unwrap_err(),
+            ErrorCode::LiquidityInsufficient.into()
+        );
+    }
+
+    #[test]
+    fn delta_amount_rounding() {
+        let liquidity = 1_000_000_000_000_000_000;
+        let sqrt_price_lower = tick_math::get_sqrt_price_at_tick(-100).unwrap();
+        let sqrt_price_upper = tick_math::get_sqrt_price_at_tick(100).unwrap();
+        for get_delta_amount in [get_delta_amount_0_unsigned, get_delta_amount_1_unsigned] {
+            let down =
+                get_delta_amount(sqrt_price_lower, sqrt_price_upper, liquidity, false).unwrap();
+            let up = get_delta_amount(sqrt_price_lower, sqrt_price_upper, liquidity, true).unwrap();
+            assert_eq!(up, down + 1);
+            // 价格的顺序不影响结果
+            assert_eq!(
+                get_delta_amount(sqrt_price_upper, sqrt_price_lower, liquidity, true).unwrap(),
+                up
+            );
+            assert_eq!(
+                get_delta_amount(sqrt_price_lower, sqrt_price_lower, liquidity, true).unwrap(),
+                0
+            );
+            assert_eq!(
+                get_delta_amount(
+                    tick_math::MIN_SQRT_PRICE_X64,
+                    tick_math::MAX_SQRT_PRICE_X64,
+                    liquidity,
+                    false
+                )
+                .unwrap_err(),
+                ErrorCode::MaxTokenOverflow.into()
+            );
+        }
+        assert_eq!(
+            get_delta_amount_0_unsigned(0, Q64, liquidity, false).unwrap_err(),
+            ErrorCode::SqrtPriceX64.into()
+        );
+    }
+}
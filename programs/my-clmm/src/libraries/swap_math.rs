@@ -0,0 +1,333 @@
+/// 单步 swap 的计算,一步之内流动性保持不变
+use crate::error::ErrorCode;
+use crate::libraries::{full_math::MulDiv, sqrt_price_math};
+use crate::states::FEE_RATE_DENOMINATOR_VALUE;
+use anchor_lang::prelude::*;
+
+/// 一步 swap 的结果
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStep {
+    /// 这一步结束后的价格
+    pub sqrt_price_next_x64: u128,
+    /// 不包含手续费的输入数量
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// 交易手续费,从输入代币中收取
+    pub fee_amount: u64,
+}
+
+/// 从 `sqrt_price_current_x64` 向 `sqrt_price_target_x64` 交易,
+/// 到达目标价格或者用完 `amount_remaining` 时停止
+///
+/// `is_base_input` 为 true 时 `amount_remaining` 是剩余的输入数量(包含手续费),否则是剩余的输出数量
+pub fn compute_swap_step(
+    sqrt_price_current_x64: u128,
+    sqrt_price_target_x64: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee_rate: u32,
+    is_base_input: bool,
+    zero_for_one: bool,
+) -> Result<SwapStep> {
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        fee_rate,
+        ErrorCode::InvalidTradeFeeRate
+    );
+    let mut swap_step = SwapStep::default();
+    // 到达目标价格需要的数量,超出 u64 时一定到达不了
+    let amount_in_range = calculate_amount_in_range(
+        sqrt_price_current_x64,
+        sqrt_price_target_x64,
+        liquidity,
+        zero_for_one,
+        is_base_input,
+    );
+    if is_base_input {
+        let amount_remaining_less_fee = amount_remaining
+            .mul_div_floor(
+                u64::from(FEE_RATE_DENOMINATOR_VALUE - fee_rate),
+                u64::from(FEE_RATE_DENOMINATOR_VALUE),
+            )
+            .ok_or(ErrorCode::CalculateOverflow)?;
+        swap_step.sqrt_price_next_x64 = match amount_in_range {
+            Some(amount_in) if amount_remaining_less_fee >= amount_in => {
+                swap_step.amount_in = amount_in;
+                sqrt_price_target_x64
+            }
+            _ => sqrt_price_math::get_next_sqrt_price_from_input(
+                sqrt_price_current_x64,
+                liquidity,
+                amount_remaining_less_fee,
+                zero_for_one,
+            )?,
+        };
+    } else {
+        swap_step.sqrt_price_next_x64 = match amount_in_range {
+            Some(amount_out) if amount_remaining >= amount_out => {
+                swap_step.amount_out = amount_out;
+                sqrt_price_target_x64
+            }
+            _ => sqrt_price_math::get_next_sqrt_price_from_output(
+                sqrt_price_current_x64,
+                liquidity,
+                amount_remaining,
+                zero_for_one,
+            )?,
+        };
+    }
+
+    // 没有到达目标价格时,根据结束价格重新计算输入和输出
+    let max = sqrt_price_target_x64 == swap_step.sqrt_price_next_x64;
+    let (price_lower, price_upper) = if zero_for_one {
+        (swap_step.sqrt_price_next_x64, sqrt_price_current_x64)
+    } else {
+        (sqrt_price_current_x64, swap_step.sqrt_price_next_x64)
+    };
+    if !(max && is_base_input) {
+        swap_step.amount_in = if zero_for_one {
+            sqrt_price_math::get_delta_amount_0_unsigned(price_lower, price_upper, liquidity, true)?
+        } else {
+            sqrt_price_math::get_delta_amount_1_unsigned(price_lower, price_upper, liquidity, true)?
+        };
+    }
+    if !(max && !is_base_input) {
+        swap_step.amount_out = if zero_for_one {
+            sqrt_price_math::get_delta_amount_1_unsigned(
+                price_lower,
+                price_upper,
+                liquidity,
+                false,
+            )?
+        } else {
+            sqrt_price_math::get_delta_amount_0_unsigned(
+                price_lower,
+                price_upper,
+                liquidity,
+                false,
+            )?
+        };
+    }
+    // 输出不能超过要求的数量
+    if !is_base_input && swap_step.amount_out > amount_remaining {
+        swap_step.amount_out = amount_remaining;
+    }
+
+    swap_step.fee_amount = if is_base_input && !max {
+        // 没有到达目标价格说明输入已经用完,剩下的都作为手续费
+        amount_remaining
+            .checked_sub(swap_step.amount_in)
+            .ok_or(ErrorCode::CalculateOverflow)?
+    } else {
+        swap_step
+            .amount_in
+            .mul_div_ceil(
+                u64::from(fee_rate),
+                u64::from(FEE_RATE_DENOMINATOR_VALUE - fee_rate),
+            )
+            .ok_or(ErrorCode::CalculateOverflow)?
+    };
+    Ok(swap_step)
+}
+
+/// 从当前价格到目标价格需要的输入数量(`is_base_input`)或者能得到的输出数量,超出 u64 时返回 None
+fn calculate_amount_in_range(
+    sqrt_price_current_x64: u128,
+    sqrt_price_target_x64: u128,
+    liquidity: u128,
+    zero_for_one: bool,
+    is_base_input: bool,
+) -> Option<u64> {
+    let result = match (zero_for_one, is_base_input) {
+        (true, true) => sqrt_price_math::get_delta_amount_0_unsigned(
+            sqrt_price_target_x64,
+            sqrt_price_current_x64,
+            liquidity,
+            true,
+        ),
+        (false, true) => sqrt_price_math::get_delta_amount_1_unsigned(
+            sqrt_price_current_x64,
+            sqrt_price_target_x64,
+            liquidity,
+            true,
+        ),
+        (true, false) => sqrt_price_math::get_delta_amount_1_unsigned(
+            sqrt_price_target_x64,
+            sqrt_price_current_x64,
+            liquidity,
+            false,
+        ),
+        (false, false) => sqrt_price_math::get_delta_amount_0_unsigned(
+            sqrt_price_current_x64,
+            sqrt_price_target_x64,
+            liquidity,
+            false,
+        ),
+    };
+    result.ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::libraries::tick_math;
+    use proptest::prelude::*;
+
+    const LIQUIDITY: u128 = 2_000_000_000_000_000_000;
+    const FEE_RATE: u32 = 600;
+
+    fn fee_of(amount_in: u64, fee_rate: u32) -> u64 {
+        amount_in
+            .mul_div_ceil(
+                u64::from(fee_rate),
+                u64::from(FEE_RATE_DENOMINATOR_VALUE - fee_rate),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn exact_in_reaches_target() {
+        let current = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let target = tick_math::get_sqrt_price_at_tick(100).unwrap();
+        let amount = 1_000_000_000_000_000_000;
+        let step =
+            compute_swap_step(current, target, LIQUIDITY, amount, FEE_RATE, true, false).unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert_eq!(
+            step.amount_in,
+            sqrt_price_math::get_delta_amount_1_unsigned(current, target, LIQUIDITY, true).unwrap()
+        );
+        assert_eq!(
+            step.amount_out,
+            sqrt_price_math::get_delta_amount_0_unsigned(current, target, LIQUIDITY, false)
+                .unwrap()
+        );
+        assert_eq!(step.fee_amount, fee_of(step.amount_in, FEE_RATE));
+        assert!(step.amount_in + step.fee_amount < amount);
+    }
+
+    #[test]
+    fn exact_in_spends_all_input() {
+        let current = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let target = tick_math::get_sqrt_price_at_tick(-10000).unwrap();
+        let amount = 1_000_000_000;
+        let step =
+            compute_swap_step(current, target, LIQUIDITY, amount, FEE_RATE, true, true).unwrap();
+        assert!(step.sqrt_price_next_x64 < current && step.sqrt_price_next_x64 > target);
+        // 没有到达目标价格时,剩下的输入都作为手续费
+        assert_eq!(step.amount_in + step.fee_amount, amount);
+        assert!(step.fee_amount >= fee_of(step.amount_in, FEE_RATE));
+
+        // 扣除手续费后不足 1 个代币,价格不变,全部作为手续费
+        let step = compute_swap_step(current, target, LIQUIDITY, 1, 3000, true, true).unwrap();
+        assert_eq!(
+            step,
+            SwapStep {
+                sqrt_price_next_x64: current,
+                amount_in: 0,
+                amount_out: 0,
+                fee_amount: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn exact_out_reaches_target() {
+        let current = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let target = tick_math::get_sqrt_price_at_tick(-100).unwrap();
+        let amount = 1_000_000_000_000_000_000;
+        let step =
+            compute_swap_step(current, target, LIQUIDITY, amount, FEE_RATE, false, true).unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert_eq!(
+            step.amount_out,
+            sqrt_price_math::get_delta_amount_1_unsigned(target, current, LIQUIDITY, false)
+                .unwrap()
+        );
+        assert!(step.amount_out < amount);
+        assert_eq!(step.fee_amount, fee_of(step.amount_in, FEE_RATE));
+    }
+
+    #[test]
+    fn exact_out_receives_requested_amount() {
+        let current = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let target = tick_math::get_sqrt_price_at_tick(10000).unwrap();
+        let amount = 1_000_000_000;
+        let step =
+            compute_swap_step(current, target, LIQUIDITY, amount, FEE_RATE, false, false).unwrap();
+        assert!(step.sqrt_price_next_x64 > current && step.sqrt_price_next_x64 < target);
+        assert_eq!(step.amount_out, amount);
+        // 输入向上取整,不少于按价格计算的输出
+        assert!(step.amount_in > amount);
+        assert_eq!(step.fee_amount, fee_of(step.amount_in, FEE_RATE));
+    }
+
+    #[test]
+    fn invalid_fee_rate() {
+        let current = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let target = tick_math::get_sqrt_price_at_tick(100).unwrap();
+        assert_eq!(
+            compute_swap_step(
+                current,
+                target,
+                LIQUIDITY,
+                1000,
+                FEE_RATE_DENOMINATOR_VALUE,
+                true,
+                false
+            )
+            .unwrap_err(),
+            ErrorCode::InvalidTradeFeeRate.into()
+        );
+    }
+
+    proptest! {
+        /// 价格的变化不超过约 1000 倍,输入和输出的数量都不会超出 u64
+        #[test]
+        fn swap_step_stays_within_bounds(
+            tick_current in -50_000..50_000i32,
+            tick_delta in 1..20_000i32,
+            liquidity in 1_000_000u128..1_000_000_000_000_000_000_000,
+            amount_remaining in 1..1_000_000_000_000_000u64,
+            fee_rate in 0..100_000u32,
+            is_base_input: bool,
+            zero_for_one: bool,
+        ) {
+            let tick_target = if zero_for_one {
+                tick_current - tick_delta
+            } else {
+                tick_current + tick_delta
+            };
+            let current = tick_math::get_sqrt_price_at_tick(tick_current).unwrap();
+            let target = tick_math::get_sqrt_price_at_tick(tick_target).unwrap();
+            let step = compute_swap_step(
+                current,
+                target,
+                liquidity,
+                amount_remaining,
+                fee_rate,
+                is_base_input,
+                zero_for_one,
+            )
+            .unwrap();
+            // 价格只会向目标移动,不会越过目标
+            if zero_for_one {
+                prop_assert!(step.sqrt_price_next_x64 <= current && step.sqrt_price_next_x64 >= target);
+            } else {
+                prop_assert!(step.sqrt_price_next_x64 >= current && step.sqrt_price_next_x64 <= target);
+            }
+            if is_base_input {
+                prop_assert!(step.amount_in + step.fee_amount <= amount_remaining);
+            } else {
+                prop_assert!(step.amount_out <= amount_remaining);
+            }
+            if step.sqrt_price_next_x64 != target {
+                if is_base_input {
+                    prop_assert_eq!(step.amount_in + step.fee_amount, amount_remaining);
+                } else {
+                    prop_assert_eq!(step.amount_out, amount_remaining);
+                }
+            }
+        }
+    }
+}
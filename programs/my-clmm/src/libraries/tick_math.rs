@@ -197,3 +197,130 @@ pub fn get_tick_at_sqrt_price(sqrt_price_x64: u128) -> Result<i32, anchor_lang::
         tick_low
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::libraries::fixed_point_64;
+    use bigdecimal::{BigDecimal, One};
+    use proptest::prelude::*;
+    use std::str::FromStr;
+
+    /// 参考值计算时保留的有效位数
+    const REFERENCE_PRECISION: u64 = 60;
+
+    /// 用高精度小数计算 sqrt(1.0001)^tick * 2^64
+    fn reference_sqrt_price_x64(tick: i32) -> BigDecimal {
+        let sqrt_10001 = BigDecimal::from_str("1.0001")
+            .unwrap()
+            .sqrt()
+            .unwrap()
+            .with_prec(REFERENCE_PRECISION);
+        let mut base = sqrt_10001;
+        let mut result = BigDecimal::one();
+        let mut exp = tick.unsigned_abs();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = (&result * &base).with_prec(REFERENCE_PRECISION);
+            }
+            base = (&base * &base).with_prec(REFERENCE_PRECISION);
+            exp >>= 1;
+        }
+        if tick < 0 {
+            result = result.inverse().with_prec(REFERENCE_PRECISION);
+        }
+        result * BigDecimal::from(fixed_point_64::Q64)
+    }
+
+    /// get_sqrt_price_at_tick 和参考值的相对误差是否不超过 2^-32
+    ///
+    /// 不能按一个 ULP 检查: 魔法因子末尾被截断成 0,只有约 50 位有效位,每次乘法后还会向下取整。
+    /// 对所有 tick 实测的最大相对误差是 2.274e-10(tick 443443,约 2^-32.03),
+    /// 这个 tick 处的绝对误差约 1.78e19,即约 2^64 个 ULP;tick <= 0 时最大约 2.2e6 个 ULP(tick -20397)。
+    /// 程序依赖的是 tick 和价格的一一对应,由下面逐个 tick 的往返和单调性测试精确检查,
+    /// 这里只确认魔法因子本身没有写错,2^-32 是覆盖实测最大误差的最小的 2 的幂
+    fn within_reference_tolerance(tick: i32) -> bool {
+        let sqrt_price_x64 = BigDecimal::from(get_sqrt_price_at_tick(tick).unwrap());
+        let reference = reference_sqrt_price_x64(tick);
+        let tolerance = &reference / BigDecimal::from(1u64 << 32);
+        (&sqrt_price_x64 - &reference).abs() <= tolerance
+    }
+
+    #[test]
+    fn sqrt_price_at_tick_worst_case_error() {
+        for tick in [443443, -20397, MIN_TICK, MAX_TICK] {
+            assert!(within_reference_tolerance(tick), "tick:{}", tick);
+        }
+    }
+
+    #[test]
+    fn sqrt_price_at_tick_round_trip_for_every_tick() {
+        // MAX_TICK 对应的价格不在 get_tick_at_sqrt_price 的定义域内,在边界测试中单独检查
+        for tick in MIN_TICK..MAX_TICK {
+            let sqrt_price_x64 = get_sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(get_tick_at_sqrt_price(sqrt_price_x64).unwrap(), tick);
+        }
+    }
+
+    #[test]
+    fn sqrt_price_at_tick_is_strictly_increasing_for_every_tick() {
+        let mut prev = get_sqrt_price_at_tick(MIN_TICK).unwrap();
+        for tick in MIN_TICK + 1..=MAX_TICK {
+            let sqrt_price_x64 = get_sqrt_price_at_tick(tick).unwrap();
+            assert!(sqrt_price_x64 > prev, "not increasing at tick {}", tick);
+            prev = sqrt_price_x64;
+        }
+    }
+
+    #[test]
+    fn min_and_max_boundary() {
+        assert_eq!(
+            get_sqrt_price_at_tick(MIN_TICK).unwrap(),
+            MIN_SQRT_PRICE_X64
+        );
+        assert_eq!(
+            get_sqrt_price_at_tick(MAX_TICK).unwrap(),
+            MAX_SQRT_PRICE_X64
+        );
+        assert!(get_sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(get_sqrt_price_at_tick(MAX_TICK + 1).is_err());
+
+        assert_eq!(
+            get_tick_at_sqrt_price(MIN_SQRT_PRICE_X64).unwrap(),
+            MIN_TICK
+        );
+        assert_eq!(
+            get_tick_at_sqrt_price(MAX_SQRT_PRICE_X64 - 1).unwrap(),
+            MAX_TICK - 1
+        );
+        assert!(get_tick_at_sqrt_price(MIN_SQRT_PRICE_X64 - 1).is_err());
+        assert!(get_tick_at_sqrt_price(MAX_SQRT_PRICE_X64).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn tick_at_sqrt_price_round_trip(tick in MIN_TICK..MAX_TICK) {
+            let sqrt_price_x64 = get_sqrt_price_at_tick(tick).unwrap();
+            prop_assert_eq!(get_tick_at_sqrt_price(sqrt_price_x64).unwrap(), tick);
+        }
+
+        #[test]
+        fn tick_at_sqrt_price_rounds_down(tick in MIN_TICK + 1..MAX_TICK) {
+            // 刚好低于 tick 边界的价格属于前一个 tick
+            let sqrt_price_x64 = get_sqrt_price_at_tick(tick).unwrap();
+            prop_assert_eq!(get_tick_at_sqrt_price(sqrt_price_x64 - 1).unwrap(), tick - 1);
+        }
+
+        #[test]
+        fn sqrt_price_at_tick_is_monotonic(tick_a in MIN_TICK..=MAX_TICK, tick_b in MIN_TICK..=MAX_TICK) {
+            let sqrt_price_a = get_sqrt_price_at_tick(tick_a).unwrap();
+            let sqrt_price_b = get_sqrt_price_at_tick(tick_b).unwrap();
+            prop_assert_eq!(tick_a.cmp(&tick_b), sqrt_price_a.cmp(&sqrt_price_b));
+        }
+
+        #[test]
+        fn sqrt_price_at_tick_matches_reference(tick in MIN_TICK..=MAX_TICK) {
+            prop_assert!(within_reference_tolerance(tick), "tick:{}", tick);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+/// tick 和 tick array 边界相关的检查
+///
+/// 每个 tick array 保存 `TICK_ARRAY_SIZE` 个 tick,相邻 tick 之间相差 tick_spacing,
+/// 所以一个 tick array 覆盖 `tick_spacing * TICK_ARRAY_SIZE` 个 tick,
+/// 它的 start index 是这个范围内的第一个 tick,必须是 `tick_spacing * TICK_ARRAY_SIZE` 的整数倍
+use crate::error::ErrorCode;
+use crate::libraries::tick_math;
+use anchor_lang::prelude::*;
+
+/// 每个 tick array 中 tick 的数量
+pub const TICK_ARRAY_SIZE: i32 = 60;
+/// 池子中 tick_array_bitmap 在正负两个方向上各自能记录的 tick array 数量
+pub const TICK_ARRAY_BITMAP_SIZE: i32 = 512;
+
+/// 检查 tick 在 [MIN_TICK, MAX_TICK] 内并且是 tick_spacing 的整数倍
+pub fn check_tick_boundary(tick: i32, tick_spacing: u16) -> Result<()> {
+    require_gte!(tick, tick_math::MIN_TICK, ErrorCode::TickLowerOverflow);
+    require_gte!(tick_math::MAX_TICK, tick, ErrorCode::TickUpperOverflow);
+    require!(
+        tick_spacing > 0 && tick % i32::from(tick_spacing) == 0,
+        ErrorCode::TickAndSpacingNotMatch
+    );
+    Ok(())
+}
+
+/// 检查 position 的 tick_lower 小于 tick_upper
+pub fn check_ticks_order(tick_lower: i32, tick_upper: i32) -> Result<()> {
+    require_gt!(tick_upper, tick_lower, ErrorCode::TickInvaildOrder);
+    Ok(())
+}
+
+/// 一个 tick array 覆盖的 tick 范围的大小
+pub fn tick_count(tick_spacing: u16) -> i32 {
+    TICK_ARRAY_SIZE * i32::from(tick_spacing)
+}
+
+/// tick 所在的 tick array 的 start index,负数向负无穷取整,
+/// 例如 tick_spacing 为 1 时 -1 所在的 tick array 从 -60 开始
+///
+/// tick_spacing 不能为 0
+pub fn get_array_start_index(tick: i32, tick_spacing: u16) -> i32 {
+    let ticks_in_array = tick_count(tick_spacing);
+    tick.div_euclid(ticks_in_array) * ticks_in_array
+}
+
+/// 检查 `start_index` 是一个合法的 tick array start index
+///
+/// MIN_TICK 所在的 tick array 的 start index 可能小于 MIN_TICK,这是唯一允许超出 tick 范围的 start index
+pub fn check_is_valid_start_index(start_index: i32, tick_spacing: u16) -> Result<()> {
+    require!(tick_spacing > 0, ErrorCode::TickAndSpacingNotMatch);
+    let is_valid = if start_index < tick_math::MIN_TICK {
+        start_index == get_array_start_index(tick_math::MIN_TICK, tick_spacing)
+    } else {
+        start_index <= tick_math::MAX_TICK && start_index % tick_count(tick_spacing) == 0
+    };
+    require!(is_valid, ErrorCode::InvalidTickArrayBoundary);
+    Ok(())
+}
+
+/// 池子中的 tick_array_bitmap 能记录的最大 tick(不包含),
+/// 超出 [-max, max) 范围的 tick array 记录在 TickArrayBitmapExtension 中
+pub fn max_tick_in_tickarray_bitmap(tick_spacing: u16) -> i32 {
+    tick_count(tick_spacing) * TICK_ARRAY_BITMAP_SIZE
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tick_boundary() {
+        assert!(check_tick_boundary(0, 1).is_ok());
+        assert!(check_tick_boundary(-120, 60).is_ok());
+        assert!(check_tick_boundary(tick_math::MIN_TICK, 1).is_ok());
+        assert!(check_tick_boundary(tick_math::MAX_TICK, 1).is_ok());
+        assert_eq!(
+            check_tick_boundary(tick_math::MIN_TICK - 1, 1).unwrap_err(),
+            ErrorCode::TickLowerOverflow.into()
+        );
+        assert_eq!(
+            check_tick_boundary(tick_math::MAX_TICK + 1, 1).unwrap_err(),
+            ErrorCode::TickUpperOverflow.into()
+        );
+        assert_eq!(
+            check_tick_boundary(61, 60).unwrap_err(),
+            ErrorCode::TickAndSpacingNotMatch.into()
+        );
+        assert!(check_tick_boundary(0, 0).is_err());
+
+        assert!(check_ticks_order(-1, 1).is_ok());
+        assert!(check_ticks_order(1, 1).is_err());
+        assert!(check_ticks_order(2, 1).is_err());
+    }
+
+    #[test]
+    fn array_start_index() {
+        assert_eq!(get_array_start_index(0, 1), 0);
+        assert_eq!(get_array_start_index(59, 1), 0);
+        assert_eq!(get_array_start_index(60, 1), 60);
+        assert_eq!(get_array_start_index(-1, 1), -60);
+        assert_eq!(get_array_start_index(-60, 1), -60);
+        assert_eq!(get_array_start_index(-61, 1), -120);
+        assert_eq!(get_array_start_index(3599, 60), 0);
+        assert_eq!(get_array_start_index(-3601, 60), -7200);
+        assert_eq!(get_array_start_index(tick_math::MIN_TICK, 1), -443640);
+        assert_eq!(get_array_start_index(tick_math::MAX_TICK, 1), 443580);
+
+        assert!(check_is_valid_start_index(0, 1).is_ok());
+        assert!(check_is_valid_start_index(-60, 1).is_ok());
+        assert!(check_is_valid_start_index(30, 1).is_err());
+        assert!(check_is_valid_start_index(-443640, 1).is_ok());
+        assert!(check_is_valid_start_index(-443700, 1).is_err());
+        assert!(check_is_valid_start_index(443580, 1).is_ok());
+        assert!(check_is_valid_start_index(443640, 1).is_err());
+        assert!(check_is_valid_start_index(0, 0).is_err());
+        // 所有 tick 所在的 tick array 都是合法的
+        for tick_spacing in [1, 10, 60, 100] {
+            for tick in [tick_math::MIN_TICK, -1, 0, 1, tick_math::MAX_TICK] {
+                let start_index = get_array_start_index(tick, tick_spacing);
+                assert!(check_is_valid_start_index(start_index, tick_spacing).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn tickarray_bitmap_range() {
+        assert_eq!(max_tick_in_tickarray_bitmap(1), 30720);
+        assert_eq!(max_tick_in_tickarray_bitmap(60), 1843200);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::ErrorCode;
 use anchor_lang::prelude::*;
 pub const AMM_CONFIG_SEED: &str = "amm_config";
 
@@ -30,16 +31,74 @@ pub struct AmmConfig {
     pub fund_fee_rate: u32,
     /// 基金所有者: 基金的地址
     pub fund_owner: Pubkey,
+    /// 从右往左的每个bit都代表一种配置的状态
+    /// bit0, 1: 禁止在该配置下创建新池子(已存在的池子不受影响), 0: normal
+    /// bit1, 1: 只有拥有 PoolCreatorPermission 的创建者才能创建池子, 0: 任何人都可以创建
+    pub status: u8,
     /// 给将来账户添加字段预留的空间,因为sol的账户空间在创建时就分配固定大小
     /// 如果将来需要添加字段，则需要预留空间
-    /// padding_u32预留 4 字节，可能用于添加新的 u32 类型字段
-    pub padding_u32: u32,
+    /// padding_u8预留 3 字节，原本是 padding_u32 中被 status 占用后剩下的部分
+    pub padding_u8: [u8; 3],
     /// padding 预留 24 字节，可能用于添加新的 u64 类型字段
     pub padding: [u64; 3],
 }
 
 impl AmmConfig {
-    pub const LEN: usize = 8 + 1 + 2 + 32 + 4 + 24;
+    /// AmmConfig 是 Borsh 序列化的账户,序列化后没有对齐填充,和 size_of 不相等,
+    /// 所以由测试检查 LEN 和序列化后的长度一致
+    pub const LEN: usize = 8 + 1 + 2 + 32 + 4 + 4 + 2 + 4 + 32 + 1 + 3 + 24;
+
+    /// 检查费率是否合法,创建和更新配置时都需要调用
+    pub fn check_fee_rates(
+        trade_fee_rate: u32,
+        protocol_fee_rate: u32,
+        fund_fee_rate: u32,
+    ) -> Result<()> {
+        require_gt!(
+            FEE_RATE_DENOMINATOR_VALUE,
+            trade_fee_rate,
+            ErrorCode::InvalidTradeFeeRate
+        );
+        require_gte!(
+            FEE_RATE_DENOMINATOR_VALUE,
+            protocol_fee_rate,
+            ErrorCode::InvalidProtocolFeeRate
+        );
+        require_gte!(
+            FEE_RATE_DENOMINATOR_VALUE,
+            fund_fee_rate,
+            ErrorCode::InvalidFundFeeRate
+        );
+        // 上面已经保证了两者都不超过 FEE_RATE_DENOMINATOR_VALUE,相加不会溢出
+        require_gte!(
+            FEE_RATE_DENOMINATOR_VALUE,
+            protocol_fee_rate + fund_fee_rate,
+            ErrorCode::InvalidFeeRateSum
+        );
+        Ok(())
+    }
+
+    //更新配置的状态
+    pub fn set_status(&mut self, status: u8) {
+        self.status = status
+    }
+
+    /// 该配置是否禁止创建新池子
+    pub fn is_create_pool_disabled(&self) -> bool {
+        self.status & (1 << AmmConfigStatusBitIndex::CreatePool as u8) != 0
+    }
+
+    /// 该配置是否只允许有权限的创建者创建池子
+    pub fn is_create_pool_permissioned(&self) -> bool {
+        self.status & (1 << AmmConfigStatusBitIndex::PermissionedCreatePool as u8) != 0
+    }
+}
+
+/// AmmConfig.status 中每个bit对应的功能
+#[derive(PartialEq, Eq)]
+pub enum AmmConfigStatusBitIndex {
+    CreatePool,
+    PermissionedCreatePool,
 }
 
 /// 当创建或更新配置时发出的事件
@@ -54,4 +113,46 @@ pub struct ConfigChangeEvent {
     pub tick_spacing: u16,
     pub fund_fee_rate: u32,
     pub fund_owner: Pubkey,
+    pub status: u8,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    /// 客户端会按偏移量读取和过滤账户数据,字段在账户数据中的偏移量不能改变
+    #[test]
+    fn amm_config_layout() {
+        let amm_config = AmmConfig {
+            bump: 0x11,
+            index: 0x2233,
+            owner: Pubkey::new_from_array([0x44; 32]),
+            protocol_fee_rate: 0x55555555,
+            trade_fee_rate: 0x66666666,
+            tick_spacing: 0x7777,
+            fund_fee_rate: 0x88888888,
+            fund_owner: Pubkey::new_from_array([0x99; 32]),
+            status: 0xaa,
+            padding_u8: [0xbb; 3],
+            padding: [0xcccccccccccccccc; 3],
+        };
+        let mut data = vec![];
+        amm_config.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), AmmConfig::LEN);
+        assert_eq!(AmmConfig::LEN, 117);
+
+        assert_eq!(data[..8], AmmConfig::DISCRIMINATOR);
+        assert_eq!(data[8], 0x11);
+        assert_eq!(data[9..11], 0x2233u16.to_le_bytes());
+        assert_eq!(data[11..43], [0x44; 32]);
+        assert_eq!(data[43..47], 0x55555555u32.to_le_bytes());
+        assert_eq!(data[47..51], 0x66666666u32.to_le_bytes());
+        assert_eq!(data[51..53], 0x7777u16.to_le_bytes());
+        assert_eq!(data[53..57], 0x88888888u32.to_le_bytes());
+        assert_eq!(data[57..89], [0x99; 32]);
+        assert_eq!(data[89], 0xaa);
+        assert_eq!(data[90..93], [0xbb; 3]);
+        assert_eq!(data[93..117], [0xcc; 24]);
+    }
 }
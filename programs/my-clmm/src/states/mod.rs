@@ -10,5 +10,14 @@ pub use oracle::*;
 pub mod tickarray_bitmap_extension;
 pub use tickarray_bitmap_extension::*;
 
+pub mod tick_array;
+pub use tick_array::*;
+
 pub mod operation_account;
 pub use operation_account::*;
+
+pub mod pool_creator_permission;
+pub use pool_creator_permission::*;
+
+pub mod support_mint_associated;
+pub use support_mint_associated::*;
@@ -1,85 +1,262 @@
+use crate::error::ErrorCode;
 use anchor_lang::prelude::*;
-use std::collections::HashSet;
+use anchor_lang::Discriminator;
 
-pub const OPERATION_SEED: &str = "operation";
-pub const OPERATION_SIZE_USIZE: usize = 10;
-pub const WHITE_MINT_SIZE_USIZE: usize = 100;
+/// 旧版本的 zero_copy 账户和现在的账户 discriminator 相同,无法按内容区分,所以换了 seed
+pub const OPERATION_SEED: &str = "operation_v2";
+/// 运营人员数量的上限
+///
+/// 账户按 Borsh 反序列化到 32KB 的 bump 堆上,且分配的内存不会释放。
+/// 新增时用 `reserve_exact` 扩容,避免 Vec 按倍数扩容,
+/// 上限保证反序列化的一份数据加上扩容时新分配的一份仍然放得下,
+/// integration-tests 中的 `operation_account_capacity_on_sbf` 在 SBF 程序上验证了这一点
+pub const MAX_OPERATION_OWNER_NUM: usize = 32;
+/// 白名单 mint 数量的上限
+pub const MAX_WHITELIST_MINT_NUM: usize = 256;
+
+/// 旧版本 zero_copy 运营账户的 seed,通过 `migrate_operation_account` 迁移到新账户
+pub const LEGACY_OPERATION_SEED: &str = "operation";
+const LEGACY_OPERATION_OWNER_NUM: usize = 10;
+const LEGACY_WHITELIST_MINT_NUM: usize = 100;
+/// 旧版本账户的大小: discriminator, bump, `[Pubkey; 10]` 运营人员, `[Pubkey; 100]` 白名单 mint
+pub const LEGACY_OPERATION_LEN: usize =
+    8 + 1 + 32 * LEGACY_OPERATION_OWNER_NUM + 32 * LEGACY_WHITELIST_MINT_NUM;
+
+/// 运营人员的角色,每个bit代表一种权限,一个运营人员可以同时拥有多个角色
+pub mod operation_role {
+    /// 可以初始化奖励,包括设置最后一个奖励槽位
+    pub const REWARD_MANAGER: u8 = 1 << 0;
+    /// 可以更新池子的状态
+    pub const POOL_PAUSER: u8 = 1 << 1;
+    /// 可以增删白名单 mint
+    pub const WHITELIST_MANAGER: u8 = 1 << 2;
+    /// 所有角色
+    pub const ALL: u8 = REWARD_MANAGER | POOL_PAUSER | WHITELIST_MANAGER;
+}
+
+/// 运营人员以及他拥有的角色
+#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Default, Debug, PartialEq, Eq)]
+pub struct OperationOwner {
+    /// Address of the operation owner
+    pub key: Pubkey,
+    /// Bitmask of `operation_role`
+    pub roles: u8,
+}
+
+impl OperationOwner {
+    pub const LEN: usize = 32 + 1;
+}
 
 /// Holds the current owner of the factory
-#[account(zero_copy(unsafe))]
-#[repr(C, packed)]
-#[derive(Debug)]
+/// 账户的大小会随着运营人员和白名单 mint 的增加而通过 realloc 扩容
+#[account]
+#[derive(Default, Debug)]
 pub struct OperationState {
     /// Bump to identify PDA
     pub bump: u8,
-    /// Address of the operation owner
-    pub operation_owners: [Pubkey; OPERATION_SIZE_USIZE],
+    /// Address of the operation owner and their roles, sorted by address
+    pub operation_owners: Vec<OperationOwner>,
     /// The mint address of whitelist to emmit reward
-    pub whitelist_mints: [Pubkey; WHITE_MINT_SIZE_USIZE],
+    pub whitelist_mints: Vec<Pubkey>,
 }
 
 impl OperationState {
-    pub const LEN: usize = 8 + 1 + 32 * OPERATION_SIZE_USIZE + 32 * WHITE_MINT_SIZE_USIZE;
+    /// 刚创建时(没有任何运营人员和白名单 mint)账户的大小
+    pub const LEN: usize = Self::space(0, 0);
+
+    /// 存放 `owner_num` 个运营人员和 `mint_num` 个白名单 mint 所需的账户大小
+    pub const fn space(owner_num: usize, mint_num: usize) -> usize {
+        8 + 1 + 4 + OperationOwner::LEN * owner_num + 4 + 32 * mint_num
+    }
+
+    /// 当前数据所需的账户大小
+    pub fn current_space(&self) -> usize {
+        Self::space(self.operation_owners.len(), self.whitelist_mints.len())
+    }
+
     pub fn initialize(&mut self, bump: u8) {
         self.bump = bump;
-        self.operation_owners = [Pubkey::default(); OPERATION_SIZE_USIZE];
-        self.whitelist_mints = [Pubkey::default(); WHITE_MINT_SIZE_USIZE];
+        self.operation_owners = Vec::new();
+        self.whitelist_mints = Vec::new();
+    }
+
+    /// 从旧版本 zero_copy 账户的数据迁移,旧版本没有角色,所有运营人员都拥有全部角色
+    pub fn migrate_from_legacy(&mut self, bump: u8, legacy_data: &[u8]) -> Result<()> {
+        require!(
+            legacy_data.len() == LEGACY_OPERATION_LEN && legacy_data[..8] == Self::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        let keys = |offset: usize, num: usize| {
+            legacy_data[offset..offset + 32 * num]
+                .chunks_exact(32)
+                .map(|key| Pubkey::try_from(key).unwrap())
+                .collect::<Vec<_>>()
+        };
+        self.initialize(bump);
+        self.update_operation_owner(keys(9, LEGACY_OPERATION_OWNER_NUM), operation_role::ALL)?;
+        self.update_whitelist_mint(keys(
+            9 + 32 * LEGACY_OPERATION_OWNER_NUM,
+            LEGACY_WHITELIST_MINT_NUM,
+        ))
     }
 
-    pub fn update_operation_owner(&mut self, keys: Vec<Pubkey>) {
-        //将固定大小的数组转换为可变的 Vec
-        let mut operation_owners = self.operation_owners.to_vec();
-        //将新的操作者公钥添加到列表中
-        operation_owners.extend(keys.as_slice().iter());
-        //移除所有等于默认公钥的项（空值）
-        operation_owners.retain(|&item| item != Pubkey::default());
-        //使用 HashSet 去除重复的公钥
-        let owners_set: HashSet<Pubkey> = HashSet::from_iter(operation_owners.iter().cloned());
-        //- 将 HashSet 转回 Vec
-        let mut updated_owner: Vec<Pubkey> = owners_set.into_iter().collect();
+    /// 添加运营人员,如果已经存在则用 `roles` 覆盖他原来的角色
+    pub fn update_operation_owner(&mut self, keys: Vec<Pubkey>, roles: u8) -> Result<()> {
+        require!(
+            roles != 0 && roles & !operation_role::ALL == 0,
+            ErrorCode::InvalidOperationRole
+        );
+        self.operation_owners.reserve_exact(keys.len());
+        for key in keys {
+            //移除所有等于默认公钥的项（空值）
+            if key == Pubkey::default() {
+                continue;
+            }
+            match self
+                .operation_owners
+                .iter_mut()
+                .find(|item| item.key == key)
+            {
+                Some(owner) => owner.roles = roles,
+                None => {
+                    require!(
+                        self.operation_owners.len() < MAX_OPERATION_OWNER_NUM,
+                        ErrorCode::OperationCapacityExhausted
+                    );
+                    self.operation_owners.push(OperationOwner { key, roles });
+                }
+            }
+        }
         //- 对公钥进行排序
-        updated_owner.sort_by(|a, b| a.cmp(b));
-        // clear 用默认值重置原数组
-        self.operation_owners = [Pubkey::default(); OPERATION_SIZE_USIZE];
-        // update 将处理后的公钥列表复制到原数组中
-        self.operation_owners[0..updated_owner.len()].copy_from_slice(updated_owner.as_slice());
+        self.operation_owners.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(())
     }
 
     pub fn remove_operation_owner(&mut self, keys: Vec<Pubkey>) {
-        let mut operation_owners = self.operation_owners.to_vec();
         // remove keys from operation_owners
-        operation_owners.retain(|x| !keys.contains(&x));
-        // clear
-        self.operation_owners = [Pubkey::default(); OPERATION_SIZE_USIZE];
-        // update
-        self.operation_owners[0..operation_owners.len()]
-            .copy_from_slice(operation_owners.as_slice());
+        self.operation_owners.retain(|x| !keys.contains(&x.key));
     }
 
-    pub fn update_whitelist_mint(&mut self, keys: Vec<Pubkey>) {
-        let mut whitelist_mints = self.whitelist_mints.to_vec();
-        whitelist_mints.extend(keys.as_slice().iter());
-        whitelist_mints.retain(|&item| item != Pubkey::default());
-        let owners_set: HashSet<Pubkey> = HashSet::from_iter(whitelist_mints.iter().cloned());
-        let updated_mints: Vec<Pubkey> = owners_set.into_iter().collect();
-        // clear
-        self.whitelist_mints = [Pubkey::default(); WHITE_MINT_SIZE_USIZE];
-        // update
-        self.whitelist_mints[0..updated_mints.len()].copy_from_slice(updated_mints.as_slice());
+    pub fn update_whitelist_mint(&mut self, keys: Vec<Pubkey>) -> Result<()> {
+        self.whitelist_mints.reserve_exact(keys.len());
+        for key in keys {
+            if key == Pubkey::default() || self.whitelist_mints.contains(&key) {
+                continue;
+            }
+            require!(
+                self.whitelist_mints.len() < MAX_WHITELIST_MINT_NUM,
+                ErrorCode::OperationCapacityExhausted
+            );
+            self.whitelist_mints.push(key);
+        }
+        Ok(())
     }
 
     pub fn remove_whitelist_mint(&mut self, keys: Vec<Pubkey>) {
-        let mut whitelist_mints = self.whitelist_mints.to_vec();
         // remove keys from whitelist_mint
-        whitelist_mints.retain(|x| !keys.contains(&x));
-        // clear
-        self.whitelist_mints = [Pubkey::default(); WHITE_MINT_SIZE_USIZE];
-        // update
-        self.whitelist_mints[0..whitelist_mints.len()].copy_from_slice(whitelist_mints.as_slice());
+        self.whitelist_mints.retain(|x| !keys.contains(x));
+    }
+
+    //判断传入的pubkey是否是拥有 `role` 中所有角色的运营人员
+    pub fn validate_operation_owner(&self, owner: Pubkey, role: u8) -> bool {
+        owner != Pubkey::default()
+            && self
+                .operation_owners
+                .iter()
+                .any(|item| item.key == owner && item.roles & role == role)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// OperationState 的大小是动态的,检查 `space` 和序列化后的长度一致,
+    /// 以及固定头部字段的偏移量
+    #[test]
+    fn operation_state_layout() {
+        assert_eq!(OperationState::LEN, 17);
+        for owner_num in 0..4 {
+            for mint_num in 0..4 {
+                let operation_state = OperationState {
+                    bump: 0x11,
+                    operation_owners: vec![
+                        OperationOwner {
+                            key: Pubkey::new_unique(),
+                            roles: operation_role::ALL,
+                        };
+                        owner_num
+                    ],
+                    whitelist_mints: vec![Pubkey::new_unique(); mint_num],
+                };
+                let mut data = vec![];
+                operation_state.try_serialize(&mut data).unwrap();
+                assert_eq!(data.len(), OperationState::space(owner_num, mint_num));
+                assert_eq!(data.len(), operation_state.current_space());
+
+                assert_eq!(data[..8], OperationState::DISCRIMINATOR);
+                assert_eq!(data[8], 0x11);
+                assert_eq!(data[9..13], (owner_num as u32).to_le_bytes());
+                let mints_offset = 13 + OperationOwner::LEN * owner_num;
+                assert_eq!(
+                    data[mints_offset..mints_offset + 4],
+                    (mint_num as u32).to_le_bytes()
+                );
+            }
+        }
     }
 
-    //判断传入的pubkey是否是运营人员
-    pub fn validate_operation_owner(&self, owner: Pubkey) -> bool {
-        owner != Pubkey::default() && self.operation_owners.contains(&owner)
+    #[test]
+    fn migrate_from_legacy() {
+        let owners = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mints = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let mut legacy_data = OperationState::DISCRIMINATOR.to_vec();
+        legacy_data.push(0x11);
+        for index in 0..LEGACY_OPERATION_OWNER_NUM {
+            // 旧版本的数组中间可能有空位
+            let key = match index {
+                0 => owners[1],
+                5 => owners[0],
+                _ => Pubkey::default(),
+            };
+            legacy_data.extend_from_slice(key.as_ref());
+        }
+        for index in 0..LEGACY_WHITELIST_MINT_NUM {
+            let key = mints.get(index).copied().unwrap_or_default();
+            legacy_data.extend_from_slice(key.as_ref());
+        }
+        assert_eq!(legacy_data.len(), LEGACY_OPERATION_LEN);
+
+        let mut operation_state = OperationState::default();
+        operation_state
+            .migrate_from_legacy(0x22, &legacy_data)
+            .unwrap();
+        assert_eq!(operation_state.bump, 0x22);
+        let mut expected_owners = owners.to_vec();
+        expected_owners.sort();
+        assert_eq!(
+            operation_state.operation_owners,
+            expected_owners
+                .into_iter()
+                .map(|key| OperationOwner {
+                    key,
+                    roles: operation_role::ALL,
+                })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(operation_state.whitelist_mints, mints.to_vec());
+
+        // 已经是新布局的数据或者长度不对
+        let mut data = vec![];
+        operation_state.try_serialize(&mut data).unwrap();
+        assert!(operation_state.migrate_from_legacy(0, &data).is_err());
+        legacy_data[0] ^= 1;
+        assert!(operation_state
+            .migrate_from_legacy(0, &legacy_data)
+            .is_err());
     }
 }
@@ -23,6 +23,8 @@ impl Observation {
     pub const LEN: usize = 4 + 8 + 8 * 4;
 }
 
+const _: () = assert!(Observation::LEN == std::mem::size_of::<Observation>());
+
 #[account(zero_copy(unsafe))]
 #[repr(C, packed)]
 #[cfg_attr(feature = "client", derive(Debug))]
@@ -59,3 +61,28 @@ impl ObservationState {
         Ok(())
     }
 }
+
+/// `LEN` 必须等于 8 字节的 discriminator 加上 `ObservationState` 的大小
+const _: () = assert!(ObservationState::LEN == 8 + std::mem::size_of::<ObservationState>());
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem::offset_of;
+
+    /// 账户数据中的偏移量还需要加上 8 字节的 discriminator
+    #[test]
+    fn observation_state_layout() {
+        assert_eq!(ObservationState::LEN, 4483);
+        assert_eq!(offset_of!(ObservationState, initialized), 0);
+        assert_eq!(offset_of!(ObservationState, recent_epoch), 1);
+        assert_eq!(offset_of!(ObservationState, observation_index), 9);
+        assert_eq!(offset_of!(ObservationState, pool_id), 11);
+        assert_eq!(offset_of!(ObservationState, observations), 43);
+        assert_eq!(offset_of!(ObservationState, padding), 4443);
+        assert_eq!(Observation::LEN, 44);
+        assert_eq!(offset_of!(Observation, block_timestamp), 0);
+        assert_eq!(offset_of!(Observation, tick_cumulative), 4);
+        assert_eq!(offset_of!(Observation, padding), 12);
+    }
+}
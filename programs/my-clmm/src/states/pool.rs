@@ -1,8 +1,9 @@
 use crate::error::ErrorCode;
+use crate::libraries::{price_math, tick_utils};
 use crate::util::get_recent_epoch;
 use anchor_lang::prelude::*;
 
-use super::{AmmConfig, OperationState};
+use super::{operation_role, AmmConfig, OperationState, TickArrayBitmapExtension};
 use anchor_spl::token_interface::Mint;
 pub const REWARD_NUM: usize = 3;
 
@@ -89,7 +90,7 @@ pub struct PoolState {
     /// 第二个奖励槽位 (index = 1):
     /// 如果池子的两个代币都还没被用作奖励，那么这个奖励必须是:
     /// 池子中的 token_0，或 token_1 或是 白名单种的token
-    /// 第三个奖励槽位 (index = 2): 需要特殊权限,只能由管理员或经过验证的操作所有者设置
+    /// 第三个奖励槽位 (index = 2): 需要特殊权限,只能由管理员或拥有 REWARD_MANAGER 角色的运营人员设置
     pub reward_infos: [RewardInfo; REWARD_NUM],
 
     ///  tick array 被初始化的位图数据结构
@@ -203,6 +204,56 @@ impl PoolState {
         self.status = status
     }
 
+    /// `bit` 对应的操作是否允许,对应的 bit 为 0 时允许
+    pub fn get_status_by_bit(&self, bit: PoolStatusBitIndex) -> bool {
+        self.status & (1 << bit as u8) == 0
+    }
+
+    /// start index 为 `tick_array_start_index` 的 tick array 是否已经初始化,
+    /// 超出池子 tick_array_bitmap 范围的从 TickArrayBitmapExtension 中查询
+    pub fn is_tick_array_initialized(
+        &self,
+        tick_array_start_index: i32,
+        tickarray_bitmap_extension: Option<&TickArrayBitmapExtension>,
+    ) -> Result<bool> {
+        tick_utils::check_is_valid_start_index(tick_array_start_index, self.tick_spacing)?;
+        let max_tick = tick_utils::max_tick_in_tickarray_bitmap(self.tick_spacing);
+        if tick_array_start_index >= max_tick || tick_array_start_index < -max_tick {
+            return tickarray_bitmap_extension
+                .ok_or(ErrorCode::MissingTickArrayBitmapExtensionAccount)?
+                .is_tick_array_initialized(tick_array_start_index, self.tick_spacing);
+        }
+        // 负方向的 tick array 记录在前 512 位,正方向的记录在后 512 位
+        let offset = (tick_array_start_index / tick_utils::tick_count(self.tick_spacing)
+            + tick_utils::TICK_ARRAY_BITMAP_SIZE) as usize;
+        Ok({ self.tick_array_bitmap }[offset / 64] & (1 << (offset % 64)) != 0)
+    }
+
+    /// 池子当前的价格,1 个 token_0 值多少个 token_1(已按 decimals 换算)
+    pub fn price(&self) -> f64 {
+        price_math::sqrt_price_x64_to_price(
+            self.sqrt_price_x64,
+            self.mint_decimals_0,
+            self.mint_decimals_1,
+        )
+    }
+
+    /// 按池子的 decimals 把价格转换成 sqrt_price_x64
+    pub fn price_to_sqrt_price_x64(&self, price: f64) -> Result<u128> {
+        price_math::price_to_sqrt_price_x64(price, self.mint_decimals_0, self.mint_decimals_1)
+    }
+
+    /// 按池子的 decimals 和 tick_spacing 把价格转换成有效的 tick,`round_up` 决定向上还是向下取
+    pub fn price_to_tick(&self, price: f64, round_up: bool) -> Result<i32> {
+        price_math::price_to_tick(
+            price,
+            self.mint_decimals_0,
+            self.mint_decimals_1,
+            self.tick_spacing,
+            round_up,
+        )
+    }
+
     pub fn initialize_reward(
         &mut self,
         open_time: u64,                   // 奖励的开始时间
@@ -240,7 +291,7 @@ impl PoolState {
         );
 
         // 获取白名单中的代币地址
-        let whitelist_mints = operation_state.whitelist_mints.to_vec();
+        let whitelist_mints = &operation_state.whitelist_mints;
 
         // 如果是倒数第二个奖励槽位
         if lowest_index == REWARD_NUM - 2 {
@@ -260,10 +311,11 @@ impl PoolState {
                 );
             }
         } else if lowest_index == REWARD_NUM - 1 {
-            // 如果是最后一个奖励槽位，确保授权地址是管理员或经过验证的操作所有者
+            // 如果是最后一个奖励槽位，确保授权地址是管理员或拥有 REWARD_MANAGER 角色的运营人员
             require!(
                 *authority == crate::admin::id()
-                    || operation_state.validate_operation_owner(*authority),
+                    || operation_state
+                        .validate_operation_owner(*authority, operation_role::REWARD_MANAGER),
                 ErrorCode::NotApproved
             );
         }
@@ -291,6 +343,35 @@ impl PoolState {
     }
 }
 
+/// `LEN` 必须等于 8 字节的 discriminator 加上 `PoolState` 的大小
+const _: () = assert!(PoolState::LEN == 8 + std::mem::size_of::<PoolState>());
+
+/// `PoolState::status` 中每个 bit 对应的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolStatusBitIndex {
+    OpenPositionOrIncreaseLiquidity,
+    DecreaseLiquidity,
+    CollectFee,
+    CollectReward,
+    Swap,
+}
+
+/// 无法识别的 bit 原样返回
+impl TryFrom<u8> for PoolStatusBitIndex {
+    type Error = u8;
+
+    fn try_from(bit: u8) -> std::result::Result<Self, u8> {
+        match bit {
+            0 => Ok(Self::OpenPositionOrIncreaseLiquidity),
+            1 => Ok(Self::DecreaseLiquidity),
+            2 => Ok(Self::CollectFee),
+            3 => Ok(Self::CollectReward),
+            4 => Ok(Self::Swap),
+            _ => Err(bit),
+        }
+    }
+}
+
 /// 奖励状态与下面的u8相对应
 #[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Debug, PartialEq)]
 /// State of reward
@@ -305,6 +386,21 @@ pub enum RewardState {
     Ended,
 }
 
+/// 无法识别的值原样返回
+impl TryFrom<u8> for RewardState {
+    type Error = u8;
+
+    fn try_from(reward_state: u8) -> std::result::Result<Self, u8> {
+        match reward_state {
+            0 => Ok(Self::Uninitialized),
+            1 => Ok(Self::Initialized),
+            2 => Ok(Self::Opening),
+            3 => Ok(Self::Ended),
+            _ => Err(reward_state),
+        }
+    }
+}
+
 #[zero_copy(unsafe)]
 #[repr(C, packed)]
 #[derive(Default, Debug, PartialEq, Eq)]
@@ -350,6 +446,8 @@ impl RewardInfo {
     }
 }
 
+const _: () = assert!(RewardInfo::LEN == std::mem::size_of::<RewardInfo>());
+
 /// Emitted when a pool is created and initialized with a starting price
 ///
 #[event]
@@ -380,3 +478,141 @@ pub struct PoolCreatedEvent {
     /// Vault of token_1
     pub token_vault_1: Pubkey,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem::offset_of;
+
+    /// 客户端会按偏移量读取和过滤账户数据,字段的偏移量不能改变
+    /// 账户数据中的偏移量还需要加上 8 字节的 discriminator
+    #[test]
+    fn pool_state_layout() {
+        assert_eq!(PoolState::LEN, 1544);
+        assert_eq!(offset_of!(PoolState, bump), 0);
+        assert_eq!(offset_of!(PoolState, amm_config), 1);
+        assert_eq!(offset_of!(PoolState, owner), 33);
+        assert_eq!(offset_of!(PoolState, token_mint_0), 65);
+        assert_eq!(offset_of!(PoolState, token_mint_1), 97);
+        assert_eq!(offset_of!(PoolState, token_vault_0), 129);
+        assert_eq!(offset_of!(PoolState, token_vault_1), 161);
+        assert_eq!(offset_of!(PoolState, observation_key), 193);
+        assert_eq!(offset_of!(PoolState, mint_decimals_0), 225);
+        assert_eq!(offset_of!(PoolState, mint_decimals_1), 226);
+        assert_eq!(offset_of!(PoolState, tick_spacing), 227);
+        assert_eq!(offset_of!(PoolState, liquidity), 229);
+        assert_eq!(offset_of!(PoolState, sqrt_price_x64), 245);
+        assert_eq!(offset_of!(PoolState, tick_current), 261);
+        assert_eq!(offset_of!(PoolState, padding3), 265);
+        assert_eq!(offset_of!(PoolState, padding4), 267);
+        assert_eq!(offset_of!(PoolState, fee_growth_global_0_x64), 269);
+        assert_eq!(offset_of!(PoolState, fee_growth_global_1_x64), 285);
+        assert_eq!(offset_of!(PoolState, protocol_fees_token_0), 301);
+        assert_eq!(offset_of!(PoolState, protocol_fees_token_1), 309);
+        assert_eq!(offset_of!(PoolState, swap_in_amount_token_0), 317);
+        assert_eq!(offset_of!(PoolState, swap_out_amount_token_1), 333);
+        assert_eq!(offset_of!(PoolState, swap_in_amount_token_1), 349);
+        assert_eq!(offset_of!(PoolState, swap_out_amount_token_0), 365);
+        assert_eq!(offset_of!(PoolState, status), 381);
+        assert_eq!(offset_of!(PoolState, padding), 382);
+        assert_eq!(offset_of!(PoolState, reward_infos), 389);
+        assert_eq!(offset_of!(PoolState, tick_array_bitmap), 896);
+        assert_eq!(offset_of!(PoolState, total_fees_token_0), 1024);
+        assert_eq!(offset_of!(PoolState, total_fees_claimed_token_0), 1032);
+        assert_eq!(offset_of!(PoolState, total_fees_token_1), 1040);
+        assert_eq!(offset_of!(PoolState, total_fees_claimed_token_1), 1048);
+        assert_eq!(offset_of!(PoolState, fund_fees_token_0), 1056);
+        assert_eq!(offset_of!(PoolState, fund_fees_token_1), 1064);
+        assert_eq!(offset_of!(PoolState, open_time), 1072);
+        assert_eq!(offset_of!(PoolState, recent_epoch), 1080);
+        assert_eq!(offset_of!(PoolState, padding1), 1088);
+        assert_eq!(offset_of!(PoolState, padding2), 1280);
+    }
+
+    #[test]
+    fn reward_info_layout() {
+        assert_eq!(RewardInfo::LEN, 169);
+        assert_eq!(offset_of!(RewardInfo, reward_state), 0);
+        assert_eq!(offset_of!(RewardInfo, open_time), 1);
+        assert_eq!(offset_of!(RewardInfo, end_time), 9);
+        assert_eq!(offset_of!(RewardInfo, last_update_time), 17);
+        assert_eq!(offset_of!(RewardInfo, emissions_per_second_x64), 25);
+        assert_eq!(offset_of!(RewardInfo, reward_total_emissioned), 41);
+        assert_eq!(offset_of!(RewardInfo, reward_claimed), 49);
+        assert_eq!(offset_of!(RewardInfo, token_mint), 57);
+        assert_eq!(offset_of!(RewardInfo, token_vault), 89);
+        assert_eq!(offset_of!(RewardInfo, authority), 121);
+        assert_eq!(offset_of!(RewardInfo, reward_growth_global_x64), 153);
+    }
+
+    #[test]
+    fn status_bit() {
+        let mut pool_state = PoolState::default();
+        assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+        pool_state.set_status(1 << 4);
+        assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+        assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity));
+        pool_state.set_status(0b1111);
+        assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+        assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::CollectReward));
+    }
+
+    #[test]
+    fn enum_from_u8() {
+        for bit in 0..5 {
+            assert_eq!(PoolStatusBitIndex::try_from(bit).unwrap() as u8, bit);
+        }
+        assert_eq!(PoolStatusBitIndex::try_from(5), Err(5));
+        assert_eq!(RewardState::try_from(2), Ok(RewardState::Opening));
+        assert_eq!(RewardState::try_from(4), Err(4));
+    }
+
+    #[test]
+    fn tick_array_bitmap() {
+        let mut pool_state = PoolState {
+            tick_spacing: 1,
+            ..Default::default()
+        };
+        // start index 为 0 的 tick array 是第 512 位,-60 是第 511 位
+        pool_state.tick_array_bitmap[8] = 1;
+        pool_state.tick_array_bitmap[7] = 1 << 63;
+        assert!(pool_state.is_tick_array_initialized(0, None).unwrap());
+        assert!(pool_state.is_tick_array_initialized(-60, None).unwrap());
+        assert!(!pool_state.is_tick_array_initialized(60, None).unwrap());
+        assert!(!pool_state.is_tick_array_initialized(-30720, None).unwrap());
+        assert!(pool_state.is_tick_array_initialized(30, None).is_err());
+        // 超出池子 bitmap 的范围需要 extension
+        assert_eq!(
+            pool_state
+                .is_tick_array_initialized(30720, None)
+                .unwrap_err(),
+            ErrorCode::MissingTickArrayBitmapExtensionAccount.into()
+        );
+        let extension = TickArrayBitmapExtension::default();
+        assert!(!pool_state
+            .is_tick_array_initialized(30720, Some(&extension))
+            .unwrap());
+    }
+
+    /// 指令的账户约束会先检查一次,这里是创建 vault 之后的再次检查
+    #[test]
+    fn reward_token_already_in_use() {
+        let mut pool_state = PoolState::default();
+        let reward_mint = Pubkey::new_unique();
+        pool_state.reward_infos[0].token_mint = reward_mint;
+        assert_eq!(
+            pool_state
+                .initialize_reward(
+                    0,
+                    reward_period_limit::MIN_REWARD_PERIOD,
+                    1,
+                    &reward_mint,
+                    &Pubkey::new_unique(),
+                    &Pubkey::new_unique(),
+                    &OperationState::default(),
+                )
+                .unwrap_err(),
+            ErrorCode::RewardTokenAlreadyInUse.into()
+        );
+    }
+}
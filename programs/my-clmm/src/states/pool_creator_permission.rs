@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+pub const POOL_CREATOR_PERMISSION_SEED: &str = "pool_creator_permission";
+
+/// 记录某个创建者可以在某个需要权限的 AmmConfig 下创建池子
+/// PDA of `[POOL_CREATOR_PERMISSION_SEED, amm_config, creator]`
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolCreatorPermission {
+    pub bump: u8,
+    /// 被授权的配置
+    pub amm_config: Pubkey,
+    /// 被授权的池子创建者
+    pub creator: Pubkey,
+    /// 为将来升级保留的padding
+    pub padding: [u64; 4],
+}
+
+impl PoolCreatorPermission {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 8 * 4;
+
+    pub fn initialize(&mut self, bump: u8, amm_config: Pubkey, creator: Pubkey) {
+        self.bump = bump;
+        self.amm_config = amm_config;
+        self.creator = creator;
+        self.padding = [0; 4];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pool_creator_permission_len() {
+        let mut data = vec![];
+        PoolCreatorPermission::default()
+            .try_serialize(&mut data)
+            .unwrap();
+        assert_eq!(data.len(), PoolCreatorPermission::LEN);
+        assert_eq!(PoolCreatorPermission::LEN, 105);
+    }
+}
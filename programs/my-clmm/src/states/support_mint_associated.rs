@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+
+pub const SUPPORT_MINT_SEED: &str = "support_mint";
+
+/// 链上的 Token-2022 mint 白名单,拥有这个账户的 mint 即使带有默认不支持的扩展,
+/// 只要这些扩展在审核时已经存在,也可以用于创建池子和奖励
+/// PDA of `[SUPPORT_MINT_SEED, mint]`
+#[account]
+#[derive(Default, Debug)]
+pub struct SupportMintAssociated {
+    pub bump: u8,
+    /// 被审核的 mint
+    pub mint: Pubkey,
+    /// 审核时 mint 带有的扩展,第 i 个 bit 对应值为 i 的 `ExtensionType`
+    pub reviewed_extensions: u64,
+    /// 为将来升级保留的padding
+    pub padding: [u64; 4],
+}
+
+impl SupportMintAssociated {
+    pub const LEN: usize = 8 + 1 + 32 + 8 + 8 * 4;
+
+    pub fn initialize(&mut self, bump: u8, mint: Pubkey, extensions: &[ExtensionType]) {
+        self.bump = bump;
+        self.mint = mint;
+        self.reviewed_extensions = extensions
+            .iter()
+            .filter_map(|e| Self::extension_bit(*e))
+            .fold(0, |mask, bit| mask | bit);
+        self.padding = [0; 4];
+    }
+
+    /// 该扩展是否在审核时已经存在
+    pub fn is_extension_reviewed(&self, extension: ExtensionType) -> bool {
+        match Self::extension_bit(extension) {
+            Some(bit) => self.reviewed_extensions & bit != 0,
+            None => false,
+        }
+    }
+
+    fn extension_bit(extension: ExtensionType) -> Option<u64> {
+        let index = u16::from(extension);
+        if index < u64::BITS as u16 {
+            Some(1 << index)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn support_mint_associated_len() {
+        let mut data = vec![];
+        SupportMintAssociated::default()
+            .try_serialize(&mut data)
+            .unwrap();
+        assert_eq!(data.len(), SupportMintAssociated::LEN);
+        assert_eq!(SupportMintAssociated::LEN, 81);
+    }
+}
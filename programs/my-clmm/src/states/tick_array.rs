@@ -0,0 +1,175 @@
+use super::REWARD_NUM;
+use crate::error::ErrorCode;
+use crate::libraries::tick_utils;
+use anchor_lang::prelude::*;
+
+pub const TICK_ARRAY_SEED: &str = "tick_array";
+pub const TICK_ARRAY_SIZE_USIZE: usize = 60;
+
+/// 一个 tick 上记录的流动性和费用数据
+#[zero_copy(unsafe)]
+#[repr(C, packed)]
+#[derive(Default, Debug)]
+pub struct TickState {
+    pub tick: i32,
+    /// 价格从左往右穿过这个 tick 时流动性的变化量,从右往左穿过时取相反数
+    pub liquidity_net: i128,
+    /// 引用这个 tick 的所有 position 的流动性之和,不为 0 时 tick 是初始化的
+    pub liquidity_gross: u128,
+
+    /// tick 另一侧每单位流动性累积的手续费,Q64.64
+    pub fee_growth_outside_0_x64: u128,
+    pub fee_growth_outside_1_x64: u128,
+
+    /// tick 另一侧每单位流动性累积的奖励,Q64.64
+    pub reward_growths_outside_x64: [u128; REWARD_NUM],
+    /// 为将来升级保留的padding
+    pub padding: [u32; 13],
+}
+
+impl TickState {
+    pub const LEN: usize = 4 + 16 + 16 + 16 + 16 + 16 * REWARD_NUM + 4 * 13;
+
+    pub fn is_initialized(&self) -> bool {
+        self.liquidity_gross != 0
+    }
+}
+
+const _: () = assert!(TickState::LEN == std::mem::size_of::<TickState>());
+
+/// PDA of `[TICK_ARRAY_SEED, pool_id, start_tick_index]`,start_tick_index 使用大端序
+///
+/// 保存从 start_tick_index 开始、间隔为 tick_spacing 的 `TICK_ARRAY_SIZE` 个 tick
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct TickArrayState {
+    pub pool_id: Pubkey,
+    pub start_tick_index: i32,
+    pub ticks: [TickState; TICK_ARRAY_SIZE_USIZE],
+    /// 已经初始化的 tick 数量,为 0 时 tick array 可以关闭
+    pub initialized_tick_count: u8,
+    // 最近一次更新epoch
+    pub recent_epoch: u64,
+    // 为将来升级保留的padding
+    pub padding: [u8; 107],
+}
+
+impl Default for TickArrayState {
+    #[inline]
+    fn default() -> TickArrayState {
+        TickArrayState {
+            pool_id: Pubkey::default(),
+            start_tick_index: 0,
+            ticks: [TickState::default(); TICK_ARRAY_SIZE_USIZE],
+            initialized_tick_count: 0,
+            recent_epoch: 0,
+            padding: [0; 107],
+        }
+    }
+}
+
+impl TickArrayState {
+    pub const LEN: usize = 8 + 32 + 4 + TickState::LEN * TICK_ARRAY_SIZE_USIZE + 1 + 8 + 107;
+
+    /// 从 `current_tick_index` 开始沿交易方向找到这个 tick array 中下一个初始化的 tick,
+    /// zero_for_one 时包含 `current_tick_index` 本身,否则从它的下一个 tick 开始。
+    /// `current_tick_index` 不在这个 tick array 中时返回 None
+    pub fn next_initialized_tick(
+        &self,
+        current_tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> Result<Option<&TickState>> {
+        let current_tick_array_start_index =
+            tick_utils::get_array_start_index(current_tick_index, tick_spacing);
+        if current_tick_array_start_index != self.start_tick_index {
+            return Ok(None);
+        }
+        let offset_in_array =
+            (current_tick_index - current_tick_array_start_index) / i32::from(tick_spacing);
+        let tick = if zero_for_one {
+            self.ticks[..=offset_in_array as usize]
+                .iter()
+                .rev()
+                .find(|tick| tick.is_initialized())
+        } else {
+            self.ticks[offset_in_array as usize + 1..]
+                .iter()
+                .find(|tick| tick.is_initialized())
+        };
+        Ok(tick)
+    }
+
+    /// 沿交易方向的第一个初始化的 tick,zero_for_one 时是最右边的,否则是最左边的
+    pub fn first_initialized_tick(&self, zero_for_one: bool) -> Result<&TickState> {
+        let tick = if zero_for_one {
+            self.ticks.iter().rev().find(|tick| tick.is_initialized())
+        } else {
+            self.ticks.iter().find(|tick| tick.is_initialized())
+        };
+        tick.ok_or_else(|| error!(ErrorCode::InvalidTickArray))
+    }
+}
+
+/// `LEN` 必须等于 8 字节的 discriminator 加上 `TickArrayState` 的大小
+const _: () = assert!(TickArrayState::LEN == 8 + std::mem::size_of::<TickArrayState>());
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem::offset_of;
+
+    /// 账户数据中的偏移量还需要加上 8 字节的 discriminator
+    #[test]
+    fn tick_array_layout() {
+        assert_eq!(TickState::LEN, 168);
+        assert_eq!(offset_of!(TickState, liquidity_net), 4);
+        assert_eq!(offset_of!(TickState, reward_growths_outside_x64), 68);
+        assert_eq!(TickArrayState::LEN, 10240);
+        assert_eq!(offset_of!(TickArrayState, start_tick_index), 32);
+        assert_eq!(offset_of!(TickArrayState, ticks), 36);
+        assert_eq!(offset_of!(TickArrayState, initialized_tick_count), 10116);
+        assert_eq!(offset_of!(TickArrayState, recent_epoch), 10117);
+    }
+
+    #[test]
+    fn find_initialized_tick() {
+        let mut tick_array = TickArrayState {
+            start_tick_index: -600,
+            ..Default::default()
+        };
+        for offset in [10, 20] {
+            let tick = &mut tick_array.ticks[offset];
+            tick.tick = -600 + offset as i32 * 10;
+            tick.liquidity_gross = 1;
+        }
+        let next = |tick, zero_for_one| {
+            tick_array
+                .next_initialized_tick(tick, 10, zero_for_one)
+                .unwrap()
+                .map(|tick| tick.tick)
+        };
+        assert_eq!(next(-400, true), Some(-400));
+        assert_eq!(next(-401, true), Some(-500));
+        assert_eq!(next(-501, true), None);
+        assert_eq!(next(-400, false), None);
+        assert_eq!(next(-500, false), Some(-400));
+        assert_eq!(next(-600, false), Some(-500));
+        // 不在这个 tick array 中
+        assert_eq!(next(0, true), None);
+        assert_eq!(next(-601, false), None);
+
+        assert_eq!(
+            { tick_array.first_initialized_tick(true).unwrap().tick },
+            -400
+        );
+        assert_eq!(
+            { tick_array.first_initialized_tick(false).unwrap().tick },
+            -500
+        );
+        assert!(TickArrayState::default()
+            .first_initialized_tick(true)
+            .is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::ErrorCode;
+use crate::libraries::tick_utils;
 use anchor_lang::prelude::*;
 const EXTENSION_TICKARRAY_BITMAP_SIZE: usize = 14;
 
@@ -42,4 +44,95 @@ impl TickArrayBitmapExtension {
         self.positive_tick_array_bitmap = [[0; 8]; EXTENSION_TICKARRAY_BITMAP_SIZE];
         self.negative_tick_array_bitmap = [[0; 8]; EXTENSION_TICKARRAY_BITMAP_SIZE];
     }
+
+    /// start index 为 `tick_array_start_index` 的 tick array 是否已经初始化,
+    /// 只能查询超出池子 tick_array_bitmap 范围的 tick array
+    pub fn is_tick_array_initialized(
+        &self,
+        tick_array_start_index: i32,
+        tick_spacing: u16,
+    ) -> Result<bool> {
+        let (bitmap_index, offset) = Self::bitmap_position(tick_array_start_index, tick_spacing)?;
+        let bitmaps = if tick_array_start_index < 0 {
+            self.negative_tick_array_bitmap
+        } else {
+            self.positive_tick_array_bitmap
+        };
+        let bitmap = bitmaps[bitmap_index];
+        Ok(bitmap[offset / 64] & (1 << (offset % 64)) != 0)
+    }
+
+    /// tick array 在第几个 bitmap 中以及在这个 bitmap 中的第几位。
+    /// 每个 bitmap 和池子的 tick_array_bitmap 一侧一样记录 512 个 tick array,
+    /// 正方向从 bitmap 的最低位往高位排列;负方向从 bitmap 的最高位往低位排列,
+    /// 离 0 最近的 tick array 在最高位
+    fn bitmap_position(tick_array_start_index: i32, tick_spacing: u16) -> Result<(usize, usize)> {
+        tick_utils::check_is_valid_start_index(tick_array_start_index, tick_spacing)?;
+        let max_tick = tick_utils::max_tick_in_tickarray_bitmap(tick_spacing);
+        require!(
+            tick_array_start_index >= max_tick || tick_array_start_index < -max_tick,
+            ErrorCode::InvalidTickArrayBoundary
+        );
+        let distance = tick_array_start_index.abs();
+        let mut bitmap_index = distance / max_tick - 1;
+        let mut offset = distance % max_tick / tick_utils::tick_count(tick_spacing);
+        if tick_array_start_index < 0 {
+            if distance % max_tick == 0 {
+                bitmap_index -= 1;
+            } else {
+                offset = tick_utils::TICK_ARRAY_BITMAP_SIZE - offset;
+            }
+        }
+        Ok((bitmap_index as usize, offset as usize))
+    }
+}
+
+/// `LEN` 必须等于 8 字节的 discriminator 加上 `TickArrayBitmapExtension` 的大小
+const _: () =
+    assert!(TickArrayBitmapExtension::LEN == 8 + std::mem::size_of::<TickArrayBitmapExtension>());
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::mem::offset_of;
+
+    /// 账户数据中的偏移量还需要加上 8 字节的 discriminator
+    #[test]
+    fn tick_array_bitmap_extension_layout() {
+        assert_eq!(TickArrayBitmapExtension::LEN, 1832);
+        assert_eq!(offset_of!(TickArrayBitmapExtension, pool_id), 0);
+        assert_eq!(
+            offset_of!(TickArrayBitmapExtension, positive_tick_array_bitmap),
+            32
+        );
+        assert_eq!(
+            offset_of!(TickArrayBitmapExtension, negative_tick_array_bitmap),
+            928
+        );
+    }
+
+    #[test]
+    fn tick_array_bitmap_position() {
+        // tick_spacing 为 1 时池子的 bitmap 记录 [-30720, 30720) 范围内的 tick array
+        let position =
+            |start_index| TickArrayBitmapExtension::bitmap_position(start_index, 1).unwrap();
+        assert_eq!(position(30720), (0, 0));
+        assert_eq!(position(30780), (0, 1));
+        assert_eq!(position(61380), (0, 511));
+        assert_eq!(position(61440), (1, 0));
+        assert_eq!(position(-30780), (0, 511));
+        assert_eq!(position(-61380), (0, 1));
+        assert_eq!(position(-61440), (0, 0));
+        assert_eq!(position(-61500), (1, 511));
+        assert_eq!(position(443580), (13, 225));
+        assert_eq!(position(-443640), (13, 286));
+        assert!(TickArrayBitmapExtension::bitmap_position(0, 1).is_err());
+        assert!(TickArrayBitmapExtension::bitmap_position(-30720, 1).is_err());
+
+        let mut extension = TickArrayBitmapExtension::default();
+        extension.negative_tick_array_bitmap[0][7] = 1 << 63;
+        assert!(extension.is_tick_array_initialized(-30780, 1).unwrap());
+        assert!(!extension.is_tick_array_initialized(30780, 1).unwrap());
+        assert!(!extension.is_tick_array_initialized(-30840, 1).unwrap());
+    }
 }
@@ -1,5 +1,119 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
 pub fn get_recent_epoch() -> Result<u64> {
     Ok(Clock::get()?.epoch)
 }
 
+/// 账户空间不够时扩容到 `new_len`,并由 payer 补足免租所需的 lamports
+pub fn realloc_account_if_needed<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if new_len <= account.data_len() {
+        return Ok(());
+    }
+    let lamports_needed = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if lamports_needed > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            lamports_needed,
+        )?;
+    }
+    account.realloc(new_len, false)?;
+    Ok(())
+}
+
+/// 创建由 `owner` 拥有、大小为 `space` 的 PDA 账户,`signer_seeds` 是这个 PDA 的种子
+///
+/// 账户中已经有 lamports 时 `create_account` 会失败,这时改为补足免租所需的 lamports 后再分配空间
+pub fn create_or_allocate_account<'info>(
+    owner: &Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    target_account: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    space: usize,
+) -> Result<()> {
+    let rent = Rent::get()?;
+    let current_lamports = target_account.lamports();
+    if current_lamports == 0 {
+        create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: target_account.clone(),
+                },
+                &[signer_seeds],
+            ),
+            rent.minimum_balance(space),
+            space as u64,
+            owner,
+        )?;
+        return Ok(());
+    }
+    let lamports_needed = rent.minimum_balance(space).saturating_sub(current_lamports);
+    if lamports_needed > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: target_account.clone(),
+                },
+            ),
+            lamports_needed,
+        )?;
+    }
+    allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: target_account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: target_account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        owner,
+    )?;
+    Ok(())
+}
+
+/// 关闭账户,lamports 转给 `destination`
+pub fn close_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = destination
+        .lamports()
+        .checked_add(account.lamports())
+        .ok_or(crate::error::ErrorCode::CalculateOverflow)?;
+    **destination.lamports.borrow_mut() = lamports;
+    **account.lamports.borrow_mut() = 0;
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use super::get_recent_epoch;
+use super::{create_or_allocate_account, get_recent_epoch};
 use crate::error::ErrorCode;
 use crate::states::*;
 use anchor_lang::{
@@ -30,7 +30,11 @@ const MINT_WHITELIST: [&'static str; 4] = [
 ];
 
 /// 检查代币是否支持
-pub fn is_supported_mint(mint_account: &InterfaceAccount<Mint>) -> Result<bool> {
+/// `support_mint_associated` 是链上白名单中该 mint 的记录,审核时已存在的扩展也视为支持
+pub fn is_supported_mint(
+    mint_account: &InterfaceAccount<Mint>,
+    support_mint_associated: Option<&SupportMintAssociated>,
+) -> Result<bool> {
     let mint_info = mint_account.to_account_info();
     // 如果是标准 SPL Token，直接返回支持
     if *mint_info.owner == Token::id() {
@@ -44,14 +48,18 @@ pub fn is_supported_mint(mint_account: &InterfaceAccount<Mint>) -> Result<bool>
     let mint_data = mint_info.try_borrow_data()?;
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
     let extensions = mint.get_extension_types()?;
+    // 只有记录的是这个 mint 时才使用链上白名单
+    let support_mint_associated =
+        support_mint_associated.filter(|item| item.mint == mint_account.key());
     // 遍历代币的所有扩展功能
     for e in extensions {
-        // 如果发现任何一个扩展功能不在这5个允许的扩展功能中，就返回 false
+        // 如果发现任何一个扩展功能不在这5个允许的扩展功能中,并且也没有在链上白名单中审核过，就返回 false
         if e != ExtensionType::TransferFeeConfig
             && e != ExtensionType::MetadataPointer
             && e != ExtensionType::TokenMetadata
             && e != ExtensionType::InterestBearingConfig
             && e != ExtensionType::MintCloseAuthority
+            && !support_mint_associated.map_or(false, |item| item.is_extension_reviewed(e))
         {
             return Ok(false);
         }
@@ -59,6 +67,54 @@ pub fn is_supported_mint(mint_account: &InterfaceAccount<Mint>) -> Result<bool>
     Ok(true)
 }
 
+/// 获取 mint 带有的所有扩展,标准 SPL Token 没有扩展
+pub fn get_mint_extension_types(
+    mint_account: &InterfaceAccount<Mint>,
+) -> Result<Vec<ExtensionType>> {
+    let mint_info = mint_account.to_account_info();
+    if *mint_info.owner == Token::id() {
+        return Ok(Vec::new());
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    Ok(mint.get_extension_types()?)
+}
+
+/// 创建 authority 为 `authority` 的 PDA token account,`signer_seeds` 是 `token_account` 的种子
+///
+/// 和 Anchor 的 `init` 一样,Token-2022 的账户会带上 mint 的扩展所要求的账户扩展
+pub fn create_token_account<'info>(
+    authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let required_extensions =
+        ExtensionType::get_required_init_account_extensions(&get_mint_extension_types(mint)?);
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(
+        &required_extensions,
+    )?;
+    create_or_allocate_account(
+        token_program.key,
+        payer,
+        system_program,
+        token_account,
+        signer_seeds,
+        space,
+    )?;
+    token_2022::initialize_account3(CpiContext::new(
+        token_program.clone(),
+        token_2022::InitializeAccount3 {
+            account: token_account.clone(),
+            mint: mint.to_account_info(),
+            authority: authority.clone(),
+        },
+    ))
+}
+
 /// 计算 Token-2022 的转账反向手续费
 pub fn get_transfer_inverse_fee(
     mint_account: Box<InterfaceAccount<Mint>>,
@@ -86,7 +142,7 @@ pub fn get_transfer_inverse_fee(
             // 这个方法会根据当前的手续费率计算出需要额外支付的手续费，以确保接收方能收到 post_fee_amount
             transfer_fee_config
                 .calculate_inverse_epoch_fee(epoch, post_fee_amount)
-                .unwrap()
+                .ok_or(ErrorCode::TransferFeeCalculateNotMatch)?
         }
     } else {
         0